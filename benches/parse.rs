@@ -0,0 +1,87 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use regex::Regex;
+use sonic_channel::protocol::Response;
+use std::sync::OnceLock;
+
+/// Parser of `QueryCommand::receive` before it was replaced by the
+/// protocol module.
+fn receive_with_regex(message: &str) -> Option<Vec<String>> {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    let re = RE.get_or_init(|| {
+        Regex::new(
+            r"(?x)
+            ^PENDING\s(?P<pending_query_id>\w+)\r\n
+            EVENT\sQUERY\s(?P<event_query_id>\w+)\s(?P<objects>.*?)\r\n$
+            ",
+        )
+        .unwrap()
+    });
+
+    let caps = re.captures(message)?;
+    if caps["pending_query_id"] != caps["event_query_id"] {
+        return None;
+    }
+    Some(
+        caps["objects"]
+            .split_whitespace()
+            .map(str::to_owned)
+            .collect(),
+    )
+}
+
+fn receive_with_protocol(message: &str) -> Option<Vec<String>> {
+    let (pending, event) = message.split_once('\n')?;
+    match (Response::parse(pending), Response::parse(event)) {
+        (Ok(Response::Pending(pending_id)), Ok(Response::Event { id, objects, .. }))
+            if pending_id == id =>
+        {
+            Some(objects.iter().map(str::to_owned).collect())
+        }
+        _ => None,
+    }
+}
+
+fn count_borrowed_objects(message: &str) -> Option<usize> {
+    let (pending, event) = message.split_once('\n')?;
+    match (Response::parse(pending), Response::parse(event)) {
+        (Ok(Response::Pending(pending_id)), Ok(Response::Event { id, objects, .. }))
+            if pending_id == id =>
+        {
+            Some(objects.iter().count())
+        }
+        _ => None,
+    }
+}
+
+fn query_response(objects: usize) -> String {
+    let objects: Vec<_> = (0..objects).map(|i| format!("recipe:{}", i)).collect();
+    format!(
+        "PENDING Bt2m2gYa\r\nEVENT QUERY Bt2m2gYa {}\r\n",
+        objects.join(" ")
+    )
+}
+
+fn bench_query_response(c: &mut Criterion) {
+    for objects in [1, 10, 100] {
+        let message = query_response(objects);
+        assert_eq!(
+            receive_with_regex(&message),
+            receive_with_protocol(&message)
+        );
+
+        let mut group = c.benchmark_group(format!("query_response/{}", objects));
+        group.bench_function("regex", |b| {
+            b.iter(|| receive_with_regex(black_box(&message)))
+        });
+        group.bench_function("protocol", |b| {
+            b.iter(|| receive_with_protocol(black_box(&message)))
+        });
+        group.bench_function("protocol_borrowed", |b| {
+            b.iter(|| count_borrowed_objects(black_box(&message)))
+        });
+        group.finish();
+    }
+}
+
+criterion_group!(benches, bench_query_response);
+criterion_main!(benches);
@@ -0,0 +1,177 @@
+use super::{ChannelMode, SonicChannel, SonicStream};
+use crate::commands::*;
+use crate::options::ChannelOptions;
+use crate::result::Result;
+use std::net::ToSocketAddrs;
+
+/// The Sonic Channel Control mode is used for administration purposes.
+/// Once in this mode, you cannot switch to other modes or gain access
+/// to commands from other modes.
+///
+/// ### Available commands
+///
+/// In this mode you can use `consolidate`, `backup`, `restore`, `info`,
+/// `ping` and `quit` commands.
+///
+/// This is the blocking version of the [`crate::ControlChannel`].
+///
+/// **Note:** This mode requires enabling the `control` and `blocking` features.
+#[derive(Debug)]
+pub struct ControlChannel(SonicStream);
+
+impl SonicChannel for ControlChannel {
+    type Channel = ControlChannel;
+
+    fn stream(&self) -> &SonicStream {
+        &self.0
+    }
+
+    fn start_with_options<A, S>(
+        addr: A,
+        password: S,
+        options: ChannelOptions,
+    ) -> Result<Self::Channel>
+    where
+        A: ToSocketAddrs,
+        S: ToString,
+    {
+        SonicStream::connect_with_start(ChannelMode::Control, addr, password, options).map(Self)
+    }
+}
+
+impl ControlChannel {
+    init_command!(
+        /// Stop connection.
+        ///
+        /// ```rust,no_run
+        /// # use sonic_channel::{blocking::*, result};
+        /// # fn main() -> result::Result<()> {
+        /// let channel = ControlChannel::start(
+        ///     "localhost:1491",
+        ///     "SecretPassword",
+        /// )?;
+        ///
+        /// channel.quit()?;
+        /// # Ok(())
+        /// # }
+        /// ```
+        blocking use QuitCommand for fn quit();
+    );
+
+    init_command!(
+        /// Ping server.
+        ///
+        /// ```rust,no_run
+        /// # use sonic_channel::{blocking::*, result};
+        /// # fn main() -> result::Result<()> {
+        /// let channel = ControlChannel::start(
+        ///     "localhost:1491",
+        ///     "SecretPassword",
+        /// )?;
+        ///
+        /// channel.ping()?;
+        /// # Ok(())
+        /// # }
+        /// ```
+        blocking use PingCommand for fn ping();
+    );
+}
+
+impl ControlChannel {
+    init_command!(
+        /// Consolidate indexed search data instead of waiting for the next automated
+        /// consolidation tick.
+        ///
+        /// Note: This method requires enabling the `control` feature and start
+        /// connection in Control mode.
+        ///
+        /// ```rust,no_run
+        /// # use sonic_channel::{blocking::*, result};
+        /// # fn main() -> result::Result<()> {
+        /// let control_channel = ControlChannel::start(
+        ///     "localhost:1491",
+        ///     "SecretPassword",
+        /// )?;
+        ///
+        /// let result = control_channel.consolidate()?;
+        /// assert_eq!(result, true);
+        /// # Ok(())
+        /// # }
+        /// ```
+        blocking use TriggerCommand for fn consolidate<'a>()
+            with { action: TriggerAction::Consolidate }
+    );
+
+    init_command!(
+        /// Backup KV + FST to <path>/<BACKUP_{KV/FST}_PATH>
+        /// See [sonic backend source code](https://github.com/valeriansaliou/sonic/blob/master/src/channel/command.rs#L808)
+        /// for more information.
+        ///
+        /// Note: This method requires enabling the `control` feature and start
+        /// connection in Control mode.
+        ///
+        /// ```rust,no_run
+        /// # use sonic_channel::{blocking::*, result};
+        /// # fn main() -> result::Result<()> {
+        /// let control_channel = ControlChannel::start(
+        ///     "localhost:1491",
+        ///     "SecretPassword",
+        /// )?;
+        ///
+        /// let result = control_channel.backup("2020-08-07T23-48")?;
+        /// assert_eq!(result, true);
+        /// # Ok(())
+        /// # }
+        /// ```
+        blocking use TriggerCommand for fn backup<'a>(
+            path: &'a str as action => TriggerAction::Backup(path),
+        );
+    );
+
+    init_command!(
+        /// Restore KV + FST from <path> if you already have backup with the same name.
+        ///
+        /// Note: This method requires enabling the `control` feature and start
+        /// connection in Control mode.
+        ///
+        /// ```rust,no_run
+        /// # use sonic_channel::{blocking::*, result};
+        /// # fn main() -> result::Result<()> {
+        /// let control_channel = ControlChannel::start(
+        ///     "localhost:1491",
+        ///     "SecretPassword",
+        /// )?;
+        ///
+        /// let result = control_channel.restore("2020-08-07T23-48")?;
+        /// assert_eq!(result, true);
+        /// # Ok(())
+        /// # }
+        /// ```
+        blocking use TriggerCommand for fn restore<'a>(
+            path: &'a str as action => TriggerAction::Restore(path),
+        );
+    );
+
+    init_command!(
+        /// Get statistics of the server: uptime, connected clients, command
+        /// latency and stores.
+        ///
+        /// Note: This method requires enabling the `control` feature and start
+        /// connection in Control mode.
+        ///
+        /// ```rust,no_run
+        /// # use sonic_channel::{blocking::*, result};
+        /// # fn main() -> result::Result<()> {
+        /// let control_channel = ControlChannel::start(
+        ///     "localhost:1491",
+        ///     "SecretPassword",
+        /// )?;
+        ///
+        /// let stats = control_channel.info()?;
+        /// dbg!(stats.uptime, stats.clients_connected);
+        /// # Ok(())
+        /// # }
+        /// ```
+        blocking use InfoCommand for fn info();
+    );
+}
@@ -0,0 +1,324 @@
+use super::{ChannelMode, SonicChannel, SonicStream};
+use crate::commands::*;
+use crate::lang::Lang;
+use crate::options::ChannelOptions;
+use crate::result::Result;
+use std::net::ToSocketAddrs;
+
+/// The Sonic Channel Ingest mode is used for altering the search index
+/// (push, pop and flush). Once in this mode, you cannot switch to other
+/// modes or gain access to commands from other modes.
+///
+/// ### Available commands
+///
+/// In this mode you can use `push`, `pop`, `flushc`, `flushb`, `flusho`,
+/// `bucket_count`, `object_count`, `word_count`, `ping` and `quit` commands.
+///
+/// This is the blocking version of the [`crate::IngestChannel`].
+///
+/// **Note:** This mode requires enabling the `ingest` and `blocking` features.
+#[derive(Debug)]
+pub struct IngestChannel(SonicStream);
+
+impl SonicChannel for IngestChannel {
+    type Channel = IngestChannel;
+
+    fn stream(&self) -> &SonicStream {
+        &self.0
+    }
+
+    fn start_with_options<A, S>(
+        addr: A,
+        password: S,
+        options: ChannelOptions,
+    ) -> Result<Self::Channel>
+    where
+        A: ToSocketAddrs,
+        S: ToString,
+    {
+        SonicStream::connect_with_start(ChannelMode::Ingest, addr, password, options).map(Self)
+    }
+}
+
+impl IngestChannel {
+    init_command!(
+        /// Stop connection.
+        ///
+        /// ```rust,no_run
+        /// # use sonic_channel::{blocking::*, result};
+        /// # fn main() -> result::Result<()> {
+        /// let channel = IngestChannel::start(
+        ///     "localhost:1491",
+        ///     "SecretPassword",
+        /// )?;
+        ///
+        /// channel.quit()?;
+        /// # Ok(())
+        /// # }
+        /// ```
+        blocking use QuitCommand for fn quit();
+    );
+
+    init_command!(
+        /// Ping server.
+        ///
+        /// ```rust,no_run
+        /// # use sonic_channel::{blocking::*, result};
+        /// # fn main() -> result::Result<()> {
+        /// let channel = IngestChannel::start(
+        ///     "localhost:1491",
+        ///     "SecretPassword",
+        /// )?;
+        ///
+        /// channel.ping()?;
+        /// # Ok(())
+        /// # }
+        /// ```
+        blocking use PingCommand for fn ping();
+    );
+}
+
+impl IngestChannel {
+    init_command!(
+        /// Push search data in the index.
+        ///
+        /// Text that doesn't fit into the server buffer is split at word
+        /// boundaries and pushed to the object by several commands.
+        ///
+        /// Note: This method requires enabling the `ingest` feature and start
+        /// connection in Ingest mode.
+        ///
+        /// ```rust,no_run
+        /// # use sonic_channel::{blocking::*, result};
+        /// # fn main() -> result::Result<()> {
+        /// let ingest_channel = IngestChannel::start(
+        ///     "localhost:1491",
+        ///     "SecretPassword",
+        /// )?;
+        ///
+        /// let dest = Collection::new("search")?
+        ///     .bucket("default")?
+        ///     .object("recipe:295")?;
+        /// let result = ingest_channel.push(dest, "Sweet Teriyaki Beef Skewers")?;
+        /// assert_eq!(result, true);
+        /// # Ok(())
+        /// # }
+        /// ```
+        blocking use PushCommand for fn push<'a>(
+            dest: ObjDest<'a>,
+            text: &'a str,
+        ) with { locale: None };
+    );
+
+    init_command!(
+        /// Push search data in the index with language of the text.
+        /// Long text is split like in `push`.
+        ///
+        /// Note: This method requires enabling the `ingest` feature and start
+        /// connection in Ingest mode.
+        ///
+        /// ```rust,no_run
+        /// # use sonic_channel::{blocking::*, result};
+        /// # fn main() -> result::Result<()> {
+        /// let ingest_channel = IngestChannel::start(
+        ///     "localhost:1491",
+        ///     "SecretPassword",
+        /// )?;
+        ///
+        /// let dest = Collection::new("search")?
+        ///     .bucket("default")?
+        ///     .object("recipe:296")?;
+        /// let result = ingest_channel.push_with_locale(dest, "Гренки с жареным картофелем и сыром", Lang::Rus)?;
+        /// assert_eq!(result, true);
+        /// # Ok(())
+        /// # }
+        /// ```
+        blocking use PushCommand for fn push_with_locale<'a>(
+            dest: ObjDest<'a>,
+            text: &'a str,
+            locale: Lang => Some(locale),
+        );
+    );
+
+    init_command!(
+        /// Pop search data from the index. Returns removed words count as usize type.
+        ///
+        /// Note: This method requires enabling the `ingest` feature and start
+        /// connection in Ingest mode.
+        ///
+        /// ```rust,no_run
+        /// # use sonic_channel::{blocking::*, result};
+        /// # fn main() -> result::Result<()> {
+        /// let ingest_channel = IngestChannel::start(
+        ///     "localhost:1491",
+        ///     "SecretPassword",
+        /// )?;
+        ///
+        /// let dest = Collection::new("search")?
+        ///     .bucket("default")?
+        ///     .object("recipe:295")?;
+        /// let result = ingest_channel.pop(dest, "beef")?;
+        /// assert_eq!(result, 1);
+        /// # Ok(())
+        /// # }
+        /// ```
+        blocking use PopCommand for fn pop<'a>(
+            dest: ObjDest<'a>,
+            text: &'a str,
+        );
+    );
+
+    init_command!(
+        /// Flush all indexed data from collections.
+        ///
+        /// Note: This method requires enabling the `ingest` feature and start
+        /// connection in Ingest mode.
+        ///
+        /// ```rust,no_run
+        /// # use sonic_channel::{blocking::*, result};
+        /// # fn main() -> result::Result<()> {
+        /// let ingest_channel = IngestChannel::start(
+        ///     "localhost:1491",
+        ///     "SecretPassword",
+        /// )?;
+        ///
+        /// let collection = Collection::new("search")?;
+        /// let flushc_count = ingest_channel.flushc(collection)?;
+        /// dbg!(flushc_count);
+        /// # Ok(())
+        /// # }
+        /// ```
+        blocking use FlushCommand for fn flushc<'a>(
+            collection: Collection<'a> as target => collection.into(),
+        );
+    );
+
+    init_command!(
+        /// Flush all indexed data from bucket in a collection.
+        ///
+        /// Note: This method requires enabling the `ingest` feature and start
+        /// connection in Ingest mode.
+        ///
+        /// ```rust,no_run
+        /// # use sonic_channel::{blocking::*, result};
+        /// # fn main() -> result::Result<()> {
+        /// let ingest_channel = IngestChannel::start(
+        ///     "localhost:1491",
+        ///     "SecretPassword",
+        /// )?;
+        ///
+        /// let dest = Collection::new("search")?.bucket("default")?;
+        /// let flushb_count = ingest_channel.flushb(dest)?;
+        /// dbg!(flushb_count);
+        /// # Ok(())
+        /// # }
+        /// ```
+        blocking use FlushCommand for fn flushb<'a>(
+            dest: Dest<'a> as target => dest.into(),
+        );
+    );
+
+    init_command!(
+        /// Flush all indexed data from an object in a bucket in collection.
+        ///
+        /// Note: This method requires enabling the `ingest` feature and start
+        /// connection in Ingest mode.
+        ///
+        /// ```rust,no_run
+        /// # use sonic_channel::{blocking::*, result};
+        /// # fn main() -> result::Result<()> {
+        /// let ingest_channel = IngestChannel::start(
+        ///     "localhost:1491",
+        ///     "SecretPassword",
+        /// )?;
+        ///
+        /// let dest = Collection::new("search")?
+        ///     .bucket("default")?
+        ///     .object("recipe:296")?;
+        /// let flusho_count = ingest_channel.flusho(dest)?;
+        /// dbg!(flusho_count);
+        /// # Ok(())
+        /// # }
+        /// ```
+        blocking use FlushCommand for fn flusho<'a>(
+            dest: ObjDest<'a> as target => dest.into(),
+        );
+    );
+
+    init_command!(
+        /// Bucket count in indexed search data of your collection.
+        ///
+        /// Note: This method requires enabling the `ingest` feature and start
+        /// connection in Ingest mode.
+        ///
+        /// ```rust,no_run
+        /// # use sonic_channel::{blocking::*, result};
+        /// # fn main() -> result::Result<()> {
+        /// let ingest_channel = IngestChannel::start(
+        ///     "localhost:1491",
+        ///     "SecretPassword",
+        /// )?;
+        ///
+        /// let collection = Collection::new("search")?;
+        /// let bucket_count = ingest_channel.bucket_count(collection)?;
+        /// dbg!(bucket_count);
+        /// # Ok(())
+        /// # }
+        /// ```
+        blocking use CountCommand for fn bucket_count<'a>(
+            collection: Collection<'a> as target => collection.into(),
+        );
+    );
+
+    init_command!(
+        /// Object count of bucket in indexed search data.
+        ///
+        /// Note: This method requires enabling the `ingest` feature and start
+        /// connection in Ingest mode.
+        ///
+        /// ```rust,no_run
+        /// # use sonic_channel::{blocking::*, result};
+        /// # fn main() -> result::Result<()> {
+        /// let ingest_channel = IngestChannel::start(
+        ///     "localhost:1491",
+        ///     "SecretPassword",
+        /// )?;
+        ///
+        /// let dest = Collection::new("search")?.bucket("default")?;
+        /// let object_count = ingest_channel.object_count(dest)?;
+        /// dbg!(object_count);
+        /// # Ok(())
+        /// # }
+        /// ```
+        blocking use CountCommand for fn object_count<'a>(
+            dest: Dest<'a> as target => dest.into(),
+        );
+    );
+
+    init_command!(
+        /// Object word count in indexed bucket search data.
+        ///
+        /// Note: This method requires enabling the `ingest` feature and start
+        /// connection in Ingest mode.
+        ///
+        /// ```rust,no_run
+        /// # use sonic_channel::{blocking::*, result};
+        /// # fn main() -> result::Result<()> {
+        /// let ingest_channel = IngestChannel::start(
+        ///     "localhost:1491",
+        ///     "SecretPassword",
+        /// )?;
+        ///
+        /// let dest = Collection::new("search")?
+        ///     .bucket("default")?
+        ///     .object("recipe:296")?;
+        /// let word_count = ingest_channel.word_count(dest)?;
+        /// dbg!(word_count);
+        /// # Ok(())
+        /// # }
+        /// ```
+        blocking use CountCommand for fn word_count<'a>(
+            dest: ObjDest<'a> as target => dest.into(),
+        );
+    );
+}
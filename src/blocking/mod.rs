@@ -0,0 +1,433 @@
+//! Blocking sonic channels.
+//!
+//! This module contains the same channels as the crate root, but every method
+//! blocks the current thread until the sonic server responds. Channels are
+//! built on top of [`std::net::TcpStream`] and don't require any async runtime.
+//!
+//! Note: This module requires enabling the `blocking` feature.
+//!
+//! ```rust,no_run
+//! use sonic_channel::blocking::*;
+//!
+//! fn main() -> sonic_channel::result::Result<()> {
+//!     let channel = SearchChannel::start(
+//!         "localhost:1491",
+//!         "SecretPassword",
+//!     )?;
+//!
+//!     let dest = Collection::new("collection")?.bucket("bucket")?;
+//!     let objects = channel.query(QueryRequest::new(dest, "recipe"))?;
+//!     dbg!(objects);
+//!
+//!     Ok(())
+//! }
+//! ```
+
+#[cfg(feature = "search")]
+mod search;
+#[cfg(feature = "search")]
+pub use search::*;
+
+#[cfg(feature = "ingest")]
+mod ingest;
+#[cfg(feature = "ingest")]
+pub use ingest::*;
+
+#[cfg(feature = "control")]
+mod control;
+#[cfg(feature = "control")]
+pub use control::*;
+
+pub use crate::channels::ChannelMode;
+#[cfg(feature = "control")]
+pub use crate::commands::ServerStats;
+#[cfg(feature = "search")]
+pub use crate::commands::{ListRequest, QueryRequest, SuggestRequest};
+pub use crate::ident::{Bucket, Collection, Dest, ObjDest, ObjectId};
+pub use crate::lang::Lang;
+pub use crate::server_info::{ServerInfo, ServerVersion};
+
+use crate::channels::interleave_address_families;
+use crate::commands::{fit_buffer, HelpCommand, StartCommand, StreamCommand};
+use crate::options::ChannelOptions;
+use crate::protocol::{is_last_line, Response};
+use crate::result::*;
+use crate::trace::{self, CommandSpan};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::net::{Shutdown, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Write buffer capacity until the server announces its buffer size.
+const UNINITIALIZED_MODE_MAX_BUFFER_SIZE: usize = 200;
+
+/// Zero socket timeout means no timeout at all.
+const MIN_SOCKET_TIMEOUT: Duration = Duration::from_millis(1);
+
+/// Blocking version of the [`crate::SonicStream`].
+///
+/// You can connect to the sonic search backend and run all supported protocol methods.
+///
+/// Stream can be shared between threads. Commands are run one by one, so
+/// each command gets its own response.
+///
+#[derive(Debug)]
+pub struct SonicStream {
+    // Read buffer lives as long as the connection, so bytes that are read
+    // ahead wait for the next command.
+    stream: Mutex<BufReader<TcpStream>>,
+    version: Option<ServerVersion>,
+    info: Option<ServerInfo>, // None – Uninitialized mode
+    options: ChannelOptions,
+    closed: AtomicBool,
+}
+
+impl SonicStream {
+    fn write(&self, stream: &TcpStream, message: &str) -> Result<()> {
+        let capacity = match &self.info {
+            Some(info) => info.max_buffer_size,
+            None => UNINITIALIZED_MODE_MAX_BUFFER_SIZE,
+        };
+        let mut writer = BufWriter::with_capacity(capacity, stream);
+        let res = writer
+            .write_all(message.as_bytes())
+            .and_then(|_| writer.flush());
+        self.guard(stream, res, ErrorKind::WriteToStream)
+    }
+
+    fn read(&self, reader: &mut BufReader<TcpStream>, max_read_lines: usize) -> Result<String> {
+        let deadline = self
+            .options
+            .read_timeout
+            .map(|timeout| Instant::now() + timeout);
+        let mut message = String::new();
+
+        let mut lines_read = 0;
+        while lines_read < max_read_lines {
+            if let Some(deadline) = deadline {
+                // Socket timeout is applied to each read call, so we shrink
+                // it to fit all lines of the response into the read timeout.
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                let res = reader
+                    .get_ref()
+                    .set_read_timeout(Some(remaining.max(MIN_SOCKET_TIMEOUT)));
+                self.guard(reader.get_ref(), res, ErrorKind::ReadStream)?;
+            }
+
+            let start = message.len();
+            let res = match reader.read_line(&mut message) {
+                Ok(0) => Err(io::ErrorKind::UnexpectedEof.into()),
+                res => res,
+            };
+            self.guard(reader.get_ref(), res, ErrorKind::ReadStream)?;
+            lines_read += 1;
+            if is_last_line(&message[start..]) {
+                break;
+            }
+        }
+
+        Ok(message)
+    }
+
+    /// Closes stream if IO operation failed, because we cannot know how many
+    /// bytes were sent or received and the next command would get the rest
+    /// of this response.
+    fn guard<T>(&self, stream: &TcpStream, res: io::Result<T>, kind: ErrorKind) -> Result<T> {
+        res.map_err(|err| {
+            self.closed.store(true, Ordering::SeqCst);
+            let _ = stream.shutdown(Shutdown::Both);
+            match err.kind() {
+                io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => {
+                    Error::new(ErrorKind::Timeout).with_source(err)
+                }
+                _ => Error::new(kind).with_source(err),
+            }
+        })
+    }
+
+    pub(crate) fn run_command<SC: StreamCommand>(&self, command: SC) -> Result<SC::Response> {
+        let mut response = None;
+        for part in command.split(self.buffer_limit())? {
+            response = Some(self.run_part(part)?);
+        }
+        response.ok_or_else(|| Error::new(ErrorKind::InvalidInput("Command has nothing to send")))
+    }
+
+    fn run_part<SC: StreamCommand>(&self, command: SC) -> Result<SC::Response> {
+        let request = self.encode(command.name(), command.message())?;
+        let mode = self.info.as_ref().map(|info| info.mode);
+        let span = CommandSpan::new(mode, &request, &self.options);
+        let res = span.in_scope(|| {
+            let message = self.exchange(&request, SC::READ_LINES_COUNT)?;
+            trace::record_response(message.len());
+            command.receive(message)
+        });
+        span.finish(res)
+    }
+
+    /// Writes the request and reads lines of its response. If the server
+    /// answered `ENDED` instead of running the command, it closes connection,
+    /// so the stream is closed too.
+    fn exchange(&self, request: &str, lines_count: usize) -> Result<String> {
+        // The lock is held for the whole command, so commands from other
+        // threads can't write their requests or read responses in between.
+        let mut stream = self
+            .stream
+            .lock()
+            .map_err(|_| Error::new(ErrorKind::WriteToStream))?;
+        if self.closed.load(Ordering::SeqCst) {
+            return Err(Error::new(ErrorKind::WriteToStream));
+        }
+
+        self.write(stream.get_ref(), request)?;
+        let message = self.read(&mut stream, lines_count)?;
+        if message.starts_with("ENDED ") {
+            self.closed.store(true, Ordering::SeqCst);
+        }
+        Ok(message)
+    }
+
+    /// Returns what the server told about itself on connect and start.
+    pub fn server_info(&self) -> &ServerInfo {
+        self.info
+            .as_ref()
+            .expect("Channels start the stream before giving it out")
+    }
+
+    /// Checks that the server supports the command and its line fits into
+    /// the server buffer, so the server won't reject it.
+    fn encode(&self, name: Option<&'static str>, message: Result<String>) -> Result<String> {
+        if let (Some(info), Some(name)) = (&self.info, name) {
+            if !info.supports(name) {
+                return Err(Error::new(ErrorKind::UnsupportedCommand((
+                    name,
+                    Some(info.mode),
+                ))));
+            }
+        }
+        fit_buffer(message?, self.buffer_limit())
+    }
+
+    /// Returns maximum length of the command line. The server announces it
+    /// on start, so commands before start are not limited.
+    fn buffer_limit(&self) -> usize {
+        match &self.info {
+            Some(info) => info.max_buffer_size,
+            None => usize::MAX,
+        }
+    }
+
+    fn connect<A: ToSocketAddrs>(addr: A, options: ChannelOptions) -> Result<Self> {
+        let connect_error = |err| Error::new(ErrorKind::ConnectToServer).with_source(err);
+        let stream = connect_tcp(addr, options.connect_timeout).map_err(connect_error)?;
+        stream
+            .set_write_timeout(options.write_timeout)
+            .map_err(connect_error)?;
+
+        let channel = SonicStream {
+            stream: Mutex::new(BufReader::new(stream)),
+            version: None,
+            info: None,
+            options,
+            closed: AtomicBool::new(false),
+        };
+
+        let message = channel.read(&mut channel.stream.lock().unwrap(), 1)?;
+        match Response::parse(&message) {
+            Ok(Response::Connected(banner)) => Ok(SonicStream {
+                version: ServerVersion::from_banner(banner),
+                ..channel
+            }),
+            _ => Err(Error::new(ErrorKind::ConnectToServer)),
+        }
+    }
+
+    fn start<S: ToString>(&mut self, mode: ChannelMode, password: S) -> Result<()> {
+        if self.info.is_some() {
+            return Err(Error::new(ErrorKind::RunCommand));
+        }
+
+        let command = StartCommand {
+            mode,
+            password: password.to_string(),
+        };
+        let response = self.run_command(command)?;
+
+        self.info = Some(ServerInfo {
+            version: self.version,
+            protocol_version: response.protocol_version,
+            max_buffer_size: response.max_buffer_size,
+            mode: response.mode,
+            commands: None,
+        });
+
+        // Servers that can't answer `HELP` are checked by version.
+        let commands = match self.run_command(HelpCommand) {
+            Ok(commands) => Some(commands),
+            Err(err) if err.is_connection_error() || err.is_timeout() => return Err(err),
+            Err(_) => None,
+        };
+        if let Some(info) = self.info.as_mut() {
+            info.commands = commands;
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn connect_with_start<A, S>(
+        mode: ChannelMode,
+        addr: A,
+        password: S,
+        options: ChannelOptions,
+    ) -> Result<Self>
+    where
+        A: ToSocketAddrs,
+        S: ToString,
+    {
+        let mut channel = Self::connect(addr, options)?;
+        channel.start(mode, password)?;
+        Ok(channel)
+    }
+}
+
+/// Tries resolved addresses one by one and returns the first connected
+/// stream or the last error.
+fn connect_tcp<A: ToSocketAddrs>(
+    addr: A,
+    connect_timeout: Option<Duration>,
+) -> io::Result<TcpStream> {
+    let mut last_err = None;
+    for addr in interleave_address_families(addr.to_socket_addrs()?) {
+        let res = match connect_timeout {
+            Some(timeout) => TcpStream::connect_timeout(&addr, timeout),
+            None => TcpStream::connect(addr),
+        };
+        match res {
+            Ok(stream) => return Ok(stream),
+            Err(err) => last_err = Some(err),
+        }
+    }
+
+    Err(last_err.unwrap_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "could not resolve to any address",
+        )
+    }))
+}
+
+/// This trait should be implemented for all supported blocking sonic channels
+pub trait SonicChannel {
+    /// Sonic channel struct
+    type Channel;
+
+    /// Returns reference for sonic stream of connection
+    fn stream(&self) -> &SonicStream;
+
+    /// Returns version of the server, buffer size, mode and commands that
+    /// the server supports. Unsupported commands fail with
+    /// `ErrorKind::UnsupportedCommand` before they are sent.
+    ///
+    /// ```rust,no_run
+    /// # use sonic_channel::blocking::*;
+    /// # fn main() -> sonic_channel::result::Result<()> {
+    /// let search_channel = SearchChannel::start(
+    ///     "localhost:1491",
+    ///     "SecretPassword",
+    /// )?;
+    ///
+    /// let info = search_channel.server_info();
+    /// if info.supports("LIST") {
+    ///     dbg!(info.version, info.max_buffer_size);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn server_info(&self) -> &ServerInfo {
+        self.stream().server_info()
+    }
+
+    /// Connects to sonic backend and run start command.
+    ///
+    /// ```rust,no_run
+    /// # use sonic_channel::blocking::*;
+    /// # fn main() -> sonic_channel::result::Result<()> {
+    /// let search_channel = SearchChannel::start(
+    ///     "localhost:1491",
+    ///     "SecretPassword",
+    /// )?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn start<A, S>(addr: A, password: S) -> Result<Self::Channel>
+    where
+        A: ToSocketAddrs,
+        S: ToString,
+    {
+        Self::start_with_options(addr, password, ChannelOptions::default())
+    }
+
+    /// Connects to sonic backend with connection options and run start command.
+    /// TLS config, reconnect policy and keepalive are ignored by blocking channels.
+    ///
+    /// ```rust,no_run
+    /// # use sonic_channel::blocking::*;
+    /// # use sonic_channel::ChannelOptions;
+    /// # use std::time::Duration;
+    /// # fn main() -> sonic_channel::result::Result<()> {
+    /// let search_channel = SearchChannel::start_with_options(
+    ///     "localhost:1491",
+    ///     "SecretPassword",
+    ///     ChannelOptions::new().read_timeout(Duration::from_secs(5)),
+    /// )?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    fn start_with_options<A, S>(
+        addr: A,
+        password: S,
+        options: ChannelOptions,
+    ) -> Result<Self::Channel>
+    where
+        A: ToSocketAddrs,
+        S: ToString;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::commands::PingCommand;
+    use crate::test_utils::{any_mode, hanging_sonic_server, scripted_sonic_server};
+
+    #[test]
+    fn close_stream_after_read_timeout() {
+        let addr = hanging_sonic_server(1);
+        let options = ChannelOptions::new().read_timeout(Duration::from_millis(100));
+        let stream = SonicStream::connect_with_start(any_mode(), addr, "secret", options).unwrap();
+
+        assert!(stream.run_command(PingCommand).unwrap());
+
+        let err = stream.run_command(PingCommand).unwrap_err();
+        assert!(err.is_timeout() && !err.is_connection_error());
+        // IO error of the socket is kept as the source.
+        assert!(std::error::Error::source(&err).is_some());
+
+        let err = stream.run_command(PingCommand).unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::WriteToStream));
+    }
+
+    #[test]
+    fn close_stream_after_ended_response() {
+        let addr = scripted_sonic_server(vec![vec!["ENDED timeout\r\n", "PONG\r\n"]]);
+        let options = ChannelOptions::new();
+        let stream = SonicStream::connect_with_start(any_mode(), addr, "secret", options).unwrap();
+
+        let err = stream.run_command(PingCommand).unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::ConnectionEnded(_)));
+
+        // The server has closed connection, so the next command isn't sent.
+        let err = stream.run_command(PingCommand).unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::WriteToStream));
+    }
+}
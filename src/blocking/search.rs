@@ -0,0 +1,177 @@
+use super::{ChannelMode, SonicChannel, SonicStream};
+use crate::commands::*;
+use crate::options::ChannelOptions;
+use crate::result::Result;
+use std::net::ToSocketAddrs;
+
+/// The Sonic Channel Search mode is used for querying the search index.
+/// Once in this mode, you cannot switch to other modes or gain access
+/// to commands from other modes.
+///
+/// ### Available commands
+///
+/// In this mode you can use `query`, `suggest`, `list`, `ping` and `quit` commands.
+///
+/// This is the blocking version of the [`crate::SearchChannel`].
+///
+/// **Note:** This mode requires enabling the `search` and `blocking` features.
+#[derive(Debug)]
+pub struct SearchChannel(SonicStream);
+
+impl SonicChannel for SearchChannel {
+    type Channel = SearchChannel;
+
+    fn stream(&self) -> &SonicStream {
+        &self.0
+    }
+
+    fn start_with_options<A, S>(
+        addr: A,
+        password: S,
+        options: ChannelOptions,
+    ) -> Result<Self::Channel>
+    where
+        A: ToSocketAddrs,
+        S: ToString,
+    {
+        SonicStream::connect_with_start(ChannelMode::Search, addr, password, options).map(Self)
+    }
+}
+
+impl SearchChannel {
+    init_command!(
+        /// Stop connection.
+        ///
+        /// ```rust,no_run
+        /// # use sonic_channel::{blocking::*, result};
+        /// # fn main() -> result::Result<()> {
+        /// let channel = SearchChannel::start(
+        ///     "localhost:1491",
+        ///     "SecretPassword",
+        /// )?;
+        ///
+        /// channel.quit()?;
+        /// # Ok(())
+        /// # }
+        /// ```
+        blocking use QuitCommand for fn quit();
+    );
+
+    init_command!(
+        /// Ping server.
+        ///
+        /// ```rust,no_run
+        /// # use sonic_channel::{blocking::*, result};
+        /// # fn main() -> result::Result<()> {
+        /// let channel = SearchChannel::start(
+        ///     "localhost:1491",
+        ///     "SecretPassword",
+        /// )?;
+        ///
+        /// channel.ping()?;
+        /// # Ok(())
+        /// # }
+        /// ```
+        blocking use PingCommand for fn ping();
+    );
+}
+
+impl SearchChannel {
+    init_command!(
+        /// Query objects in database. Request sets the terms and optional
+        /// limit, offset and language, see [`QueryRequest`].
+        ///
+        /// Terms that don't fit into the server buffer are not sent, the
+        /// method fails with `ErrorKind::CommandTooLong`.
+        ///
+        /// Note: This method requires enabling the `search` feature and start
+        /// connection in Search mode.
+        ///
+        /// ```rust,no_run
+        /// # use sonic_channel::{blocking::*, result};
+        /// # fn main() -> result::Result<()> {
+        /// let search_channel = SearchChannel::start(
+        ///     "localhost:1491",
+        ///     "SecretPassword",
+        /// )?;
+        ///
+        /// let dest = Collection::new("search")?.bucket("default")?;
+        /// let result = search_channel
+        ///     .query(QueryRequest::new(dest, "Beef").limit(10).lang(Lang::Eng))?;
+        /// dbg!(result);
+        /// # Ok(())
+        /// # }
+        /// ```
+        blocking use QueryCommand for fn query<'a>(
+            req: QueryRequest<'a>,
+        );
+    );
+
+    init_command!(
+        /// Suggest auto-completes words. Request sets the word and optional
+        /// limit, see [`SuggestRequest`].
+        ///
+        /// Note: This method requires enabling the `search` feature and start
+        /// connection in Search mode.
+        ///
+        /// ```rust,no_run
+        /// # use sonic_channel::{blocking::*, result};
+        /// # fn main() -> result::Result<()> {
+        /// let search_channel = SearchChannel::start(
+        ///     "localhost:1491",
+        ///     "SecretPassword",
+        /// )?;
+        ///
+        /// let dest = Collection::new("search")?.bucket("default")?;
+        /// let result = search_channel.suggest(SuggestRequest::new(dest, "Beef"))?;
+        /// dbg!(result);
+        /// # Ok(())
+        /// # }
+        /// ```
+        blocking use SuggestCommand for fn suggest<'a>(
+            req: SuggestRequest<'a>,
+        );
+    );
+
+    init_command!(
+        /// List indexed words of the bucket. Request sets optional limit and
+        /// offset, see [`ListRequest`].
+        ///
+        /// Note: This method requires enabling the `search` feature and start
+        /// connection in Search mode. Sonic supports `LIST` since v1.4.0.
+        ///
+        /// ```rust,no_run
+        /// # use sonic_channel::{blocking::*, result};
+        /// # fn main() -> result::Result<()> {
+        /// let search_channel = SearchChannel::start(
+        ///     "localhost:1491",
+        ///     "SecretPassword",
+        /// )?;
+        ///
+        /// let dest = Collection::new("search")?.bucket("default")?;
+        /// let words = search_channel.list(ListRequest::new(dest).limit(100))?;
+        /// dbg!(words);
+        /// # Ok(())
+        /// # }
+        /// ```
+        blocking use ListCommand for fn list<'a>(
+            req: ListRequest<'a>,
+        );
+    );
+
+    /// List all indexed words of the bucket page by page with `LIST`
+    /// commands of the given page size.
+    ///
+    /// Note: This method requires enabling the `search` feature and start
+    /// connection in Search mode. Sonic supports `LIST` since v1.4.0.
+    pub fn list_all(&self, dest: Dest<'_>, page_size: usize) -> Result<Vec<String>> {
+        let mut words = Vec::new();
+        let mut next = Some(ListRequest::new(dest).limit(page_size));
+        while let Some(req) = next {
+            let page = self.list(req)?;
+            next = req.next_page(page.len());
+            words.extend(page);
+        }
+        Ok(words)
+    }
+}
@@ -1,8 +1,10 @@
-use super::{ChannelMode, SonicChannel, SonicStream};
+use super::{ChannelMode, Pipeline, SonicChannel, SonicStream, Transport};
 use crate::commands::*;
+use crate::options::ChannelOptions;
 use crate::result::Result;
 use async_trait::*;
-use std::net::SocketAddr;
+#[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
+use std::net::ToSocketAddrs;
 
 /// The Sonic Channel Control mode is used for administration purposes.
 /// Once in this mode, you cannot switch to other modes or gain access
@@ -10,7 +12,7 @@ use std::net::SocketAddr;
 ///
 /// ### Available commands
 ///
-/// In this mode you can use `consolidate`, `backup`, `restore`,
+/// In this mode you can use `consolidate`, `backup`, `restore`, `info`,
 /// `ping` and `quit` commands.
 ///
 /// **Note:** This mode requires enabling the `control` feature.
@@ -25,12 +27,31 @@ impl SonicChannel for ControlChannel {
         &self.0
     }
 
-    async fn start<A, S>(addr: A, password: S) -> Result<Self::Channel>
+    #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
+    async fn start_with_options<A, S>(
+        addr: A,
+        password: S,
+        options: ChannelOptions,
+    ) -> Result<Self::Channel>
     where
-        A: Into<SocketAddr> + Send + 'static,
+        A: ToSocketAddrs + Clone + Send + Sync + 'static,
         S: ToString + Send + 'static,
     {
-        SonicStream::connect_with_start(ChannelMode::Control, addr, password)
+        SonicStream::connect_with_start(ChannelMode::Control, addr, password, options)
+            .await
+            .map(Self)
+    }
+
+    async fn start_with_transport_and_options<T, S>(
+        io: T,
+        password: S,
+        options: ChannelOptions,
+    ) -> Result<Self::Channel>
+    where
+        T: Transport,
+        S: ToString + Send + 'static,
+    {
+        SonicStream::start_with_transport(ChannelMode::Control, io, password, options)
             .await
             .map(Self)
     }
@@ -42,15 +63,21 @@ impl ControlChannel {
         ///
         /// ```rust,no_run
         /// # use sonic_channel::*;
+        /// # #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
         /// # fn main() -> result::Result<()> {
+        /// # futures_lite::future::block_on(async {
         /// let channel = ControlChannel::start(
         ///     "localhost:1491",
         ///     "SecretPassword",
-        /// )?;
+        /// ).await?;
         ///
-        /// channel.quit()?;
+        /// channel.quit().await?;
         /// # Ok(())
+        /// # })
         /// # }
+        /// # #[cfg(not(any(feature = "runtime-tokio", feature = "rt-async-io")))]
+        /// # fn main() {}
+        /// ```
         use QuitCommand for fn quit();
     );
 
@@ -59,15 +86,21 @@ impl ControlChannel {
         ///
         /// ```rust,no_run
         /// # use sonic_channel::*;
+        /// # #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
         /// # fn main() -> result::Result<()> {
+        /// # futures_lite::future::block_on(async {
         /// let channel = ControlChannel::start(
         ///     "localhost:1491",
         ///     "SecretPassword",
-        /// )?;
+        /// ).await?;
         ///
-        /// channel.ping()?;
+        /// channel.ping().await?;
         /// # Ok(())
+        /// # })
         /// # }
+        /// # #[cfg(not(any(feature = "runtime-tokio", feature = "rt-async-io")))]
+        /// # fn main() {}
+        /// ```
         use PingCommand for fn ping();
     );
 }
@@ -82,18 +115,24 @@ impl ControlChannel {
         ///
         /// ```rust,no_run
         /// # use sonic_channel::*;
+        /// # #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
         /// # fn main() -> result::Result<()> {
+        /// # futures_lite::future::block_on(async {
         /// let control_channel = ControlChannel::start(
         ///     "localhost:1491",
         ///     "SecretPassword",
-        /// )?;
+        /// ).await?;
         ///
-        /// let result = control_channel.consolidate()?;
+        /// let result = control_channel.consolidate().await?;
         /// assert_eq!(result, true);
         /// # Ok(())
+        /// # })
         /// # }
+        /// # #[cfg(not(any(feature = "runtime-tokio", feature = "rt-async-io")))]
+        /// # fn main() {}
         /// ```
         use TriggerCommand for fn consolidate<'a>()
+            with { action: TriggerAction::Consolidate }
     );
 
     init_command!(
@@ -106,21 +145,24 @@ impl ControlChannel {
         ///
         /// ```rust,no_run
         /// # use sonic_channel::*;
+        /// # #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
         /// # fn main() -> result::Result<()> {
+        /// # futures_lite::future::block_on(async {
         /// let control_channel = ControlChannel::start(
         ///     "localhost:1491",
         ///     "SecretPassword",
-        /// )?;
+        /// ).await?;
         ///
-        /// let result = control_channel.backup("2020-08-07T23-48")?;
+        /// let result = control_channel.backup("2020-08-07T23-48").await?;
         /// assert_eq!(result, true);
         /// # Ok(())
+        /// # })
         /// # }
+        /// # #[cfg(not(any(feature = "runtime-tokio", feature = "rt-async-io")))]
+        /// # fn main() {}
         /// ```
         use TriggerCommand for fn backup<'a>(
-            // It's not action, but my macro cannot support alias for custom argument.
-            // TODO: Add alias to macro and rename argument of this function.
-            action: &'a str => TriggerAction::Backup(action),
+            path: &'a str as action => TriggerAction::Backup(path),
         );
     );
 
@@ -132,21 +174,74 @@ impl ControlChannel {
         ///
         /// ```rust,no_run
         /// # use sonic_channel::*;
+        /// # #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
         /// # fn main() -> result::Result<()> {
+        /// # futures_lite::future::block_on(async {
         /// let control_channel = ControlChannel::start(
         ///     "localhost:1491",
         ///     "SecretPassword",
-        /// )?;
+        /// ).await?;
         ///
-        /// let result = control_channel.restore("2020-08-07T23-48")?;
+        /// let result = control_channel.restore("2020-08-07T23-48").await?;
         /// assert_eq!(result, true);
         /// # Ok(())
+        /// # })
         /// # }
+        /// # #[cfg(not(any(feature = "runtime-tokio", feature = "rt-async-io")))]
+        /// # fn main() {}
         /// ```
         use TriggerCommand for fn restore<'a>(
-            // It's not action, but my macro cannot support alias for custom argument.
-            // TODO: Add alias to macro and rename argument of this function.
-            action: &'a str => TriggerAction::Restore(action),
+            path: &'a str as action => TriggerAction::Restore(path),
+        );
+    );
+
+    init_command!(
+        /// Get statistics of the server: uptime, connected clients, command
+        /// latency and stores.
+        ///
+        /// Note: This method requires enabling the `control` feature and start
+        /// connection in Control mode.
+        ///
+        /// ```rust,no_run
+        /// # use sonic_channel::*;
+        /// # #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
+        /// # fn main() -> result::Result<()> {
+        /// # futures_lite::future::block_on(async {
+        /// let control_channel = ControlChannel::start(
+        ///     "localhost:1491",
+        ///     "SecretPassword",
+        /// ).await?;
+        ///
+        /// let stats = control_channel.info().await?;
+        /// dbg!(stats.uptime, stats.clients_connected);
+        /// # Ok(())
+        /// # })
+        /// # }
+        /// # #[cfg(not(any(feature = "runtime-tokio", feature = "rt-async-io")))]
+        /// # fn main() {}
+        /// ```
+        use InfoCommand for fn info();
+    );
+}
+
+impl<'a> Pipeline<'a, ControlChannel> {
+    init_command!(
+        /// Consolidate indexed search data.
+        pipeline use TriggerCommand for fn consolidate<'a>()
+            with { action: TriggerAction::Consolidate }
+    );
+
+    init_command!(
+        /// Backup KV + FST to <path>/<BACKUP_{KV/FST}_PATH>
+        pipeline use TriggerCommand for fn backup<'a>(
+            path: &'a str as action => TriggerAction::Backup(path),
+        );
+    );
+
+    init_command!(
+        /// Restore KV + FST from <path> if you already have backup with the same name.
+        pipeline use TriggerCommand for fn restore<'a>(
+            path: &'a str as action => TriggerAction::Restore(path),
         );
     );
 }
@@ -1,8 +1,11 @@
-use super::{ChannelMode, SonicChannel, SonicStream};
+use super::{ChannelMode, Pipeline, SonicChannel, SonicStream, Transport};
 use crate::commands::*;
+use crate::lang::Lang;
+use crate::options::ChannelOptions;
 use crate::result::Result;
 use async_trait::*;
-use std::net::SocketAddr;
+#[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
+use std::net::ToSocketAddrs;
 
 /// The Sonic Channel Ingest mode is used for altering the search index
 /// (push, pop and flush). Once in this mode, you cannot switch to other
@@ -25,12 +28,31 @@ impl SonicChannel for IngestChannel {
         &self.0
     }
 
-    async fn start<A, S>(addr: A, password: S) -> Result<Self::Channel>
+    #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
+    async fn start_with_options<A, S>(
+        addr: A,
+        password: S,
+        options: ChannelOptions,
+    ) -> Result<Self::Channel>
     where
-        A: Into<SocketAddr> + Send + 'static,
+        A: ToSocketAddrs + Clone + Send + Sync + 'static,
         S: ToString + Send + 'static,
     {
-        SonicStream::connect_with_start(ChannelMode::Ingest, addr, password)
+        SonicStream::connect_with_start(ChannelMode::Ingest, addr, password, options)
+            .await
+            .map(Self)
+    }
+
+    async fn start_with_transport_and_options<T, S>(
+        io: T,
+        password: S,
+        options: ChannelOptions,
+    ) -> Result<Self::Channel>
+    where
+        T: Transport,
+        S: ToString + Send + 'static,
+    {
+        SonicStream::start_with_transport(ChannelMode::Ingest, io, password, options)
             .await
             .map(Self)
     }
@@ -42,15 +64,21 @@ impl IngestChannel {
         ///
         /// ```rust,no_run
         /// # use sonic_channel::*;
+        /// # #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
         /// # fn main() -> result::Result<()> {
+        /// # futures_lite::future::block_on(async {
         /// let channel = IngestChannel::start(
         ///     "localhost:1491",
         ///     "SecretPassword",
-        /// )?;
+        /// ).await?;
         ///
-        /// channel.quit()?;
+        /// channel.quit().await?;
         /// # Ok(())
+        /// # })
         /// # }
+        /// # #[cfg(not(any(feature = "runtime-tokio", feature = "rt-async-io")))]
+        /// # fn main() {}
+        /// ```
         use QuitCommand for fn quit();
     );
 
@@ -59,15 +87,21 @@ impl IngestChannel {
         ///
         /// ```rust,no_run
         /// # use sonic_channel::*;
+        /// # #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
         /// # fn main() -> result::Result<()> {
+        /// # futures_lite::future::block_on(async {
         /// let channel = IngestChannel::start(
         ///     "localhost:1491",
         ///     "SecretPassword",
-        /// )?;
+        /// ).await?;
         ///
-        /// channel.ping()?;
+        /// channel.ping().await?;
         /// # Ok(())
+        /// # })
         /// # }
+        /// # #[cfg(not(any(feature = "runtime-tokio", feature = "rt-async-io")))]
+        /// # fn main() {}
+        /// ```
         use PingCommand for fn ping();
     );
 }
@@ -76,66 +110,71 @@ impl IngestChannel {
     init_command!(
         /// Push search data in the index.
         ///
+        /// Text that doesn't fit into the server buffer is split at word
+        /// boundaries and pushed to the object by several commands.
+        ///
         /// Note: This method requires enabling the `ingest` feature and start
         /// connection in Ingest mode.
         ///
         /// ```rust,no_run
         /// # use sonic_channel::*;
+        /// # #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
         /// # fn main() -> result::Result<()> {
+        /// # futures_lite::future::block_on(async {
         /// let ingest_channel = IngestChannel::start(
         ///     "localhost:1491",
         ///     "SecretPassword",
-        /// )?;
-        ///
-        /// let result = ingest_channel.push(
-        ///     "search",
-        ///     "default",
-        ///     "recipe:295",
-        ///     "Sweet Teriyaki Beef Skewers",
-        /// )?;
+        /// ).await?;
+        ///
+        /// let dest = Collection::new("search")?
+        ///     .bucket("default")?
+        ///     .object("recipe:295")?;
+        /// let result = ingest_channel.push(dest, "Sweet Teriyaki Beef Skewers").await?;
         /// assert_eq!(result, true);
         /// # Ok(())
+        /// # })
         /// # }
+        /// # #[cfg(not(any(feature = "runtime-tokio", feature = "rt-async-io")))]
+        /// # fn main() {}
         /// ```
         use PushCommand for fn push<'a>(
-            collection: &'a str,
-            bucket: &'a str,
-            object: &'a str,
+            dest: ObjDest<'a>,
             text: &'a str,
-        );
+        ) with { locale: None };
     );
 
     init_command!(
-        /// Push search data in the index with locale parameter in ISO 639-3 code.
+        /// Push search data in the index with language of the text.
+        /// Long text is split like in `push`.
         ///
         /// Note: This method requires enabling the `ingest` feature and start
         /// connection in Ingest mode.
         ///
         /// ```rust,no_run
         /// # use sonic_channel::*;
+        /// # #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
         /// # fn main() -> result::Result<()> {
+        /// # futures_lite::future::block_on(async {
         /// let ingest_channel = IngestChannel::start(
         ///     "localhost:1491",
         ///     "SecretPassword",
-        /// )?;
-        ///
-        /// let result = ingest_channel.push_with_locale(
-        ///     "search",
-        ///     "default",
-        ///     "recipe:296",
-        ///     "Гренки с жареным картофелем и сыром",
-        ///     "rus",
-        /// )?;
+        /// ).await?;
+        ///
+        /// let dest = Collection::new("search")?
+        ///     .bucket("default")?
+        ///     .object("recipe:296")?;
+        /// let result = ingest_channel.push_with_locale(dest, "Гренки с жареным картофелем и сыром", Lang::Rus).await?;
         /// assert_eq!(result, true);
         /// # Ok(())
+        /// # })
         /// # }
+        /// # #[cfg(not(any(feature = "runtime-tokio", feature = "rt-async-io")))]
+        /// # fn main() {}
         /// ```
         use PushCommand for fn push_with_locale<'a>(
-            collection: &'a str,
-            bucket: &'a str,
-            object: &'a str,
+            dest: ObjDest<'a>,
             text: &'a str,
-            locale: &'a str => Some(locale),
+            locale: Lang => Some(locale),
         );
     );
 
@@ -147,21 +186,27 @@ impl IngestChannel {
         ///
         /// ```rust,no_run
         /// # use sonic_channel::*;
+        /// # #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
         /// # fn main() -> result::Result<()> {
+        /// # futures_lite::future::block_on(async {
         /// let ingest_channel = IngestChannel::start(
         ///     "localhost:1491",
         ///     "SecretPassword",
-        /// )?;
+        /// ).await?;
         ///
-        /// let result = ingest_channel.pop("search", "default", "recipe:295", "beef")?;
+        /// let dest = Collection::new("search")?
+        ///     .bucket("default")?
+        ///     .object("recipe:295")?;
+        /// let result = ingest_channel.pop(dest, "beef").await?;
         /// assert_eq!(result, 1);
         /// # Ok(())
+        /// # })
         /// # }
+        /// # #[cfg(not(any(feature = "runtime-tokio", feature = "rt-async-io")))]
+        /// # fn main() {}
         /// ```
         use PopCommand for fn pop<'a>(
-            collection: &'a str,
-            bucket: &'a str,
-            object: &'a str,
+            dest: ObjDest<'a>,
             text: &'a str,
         );
     );
@@ -174,19 +219,25 @@ impl IngestChannel {
         ///
         /// ```rust,no_run
         /// # use sonic_channel::*;
+        /// # #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
         /// # fn main() -> result::Result<()> {
+        /// # futures_lite::future::block_on(async {
         /// let ingest_channel = IngestChannel::start(
         ///     "localhost:1491",
         ///     "SecretPassword",
-        /// )?;
+        /// ).await?;
         ///
-        /// let flushc_count = ingest_channel.flushc("search")?;
+        /// let collection = Collection::new("search")?;
+        /// let flushc_count = ingest_channel.flushc(collection).await?;
         /// dbg!(flushc_count);
         /// # Ok(())
+        /// # })
         /// # }
+        /// # #[cfg(not(any(feature = "runtime-tokio", feature = "rt-async-io")))]
+        /// # fn main() {}
         /// ```
         use FlushCommand for fn flushc<'a>(
-            collection: &'a str,
+            collection: Collection<'a> as target => collection.into(),
         );
     );
 
@@ -198,20 +249,25 @@ impl IngestChannel {
         ///
         /// ```rust,no_run
         /// # use sonic_channel::*;
+        /// # #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
         /// # fn main() -> result::Result<()> {
+        /// # futures_lite::future::block_on(async {
         /// let ingest_channel = IngestChannel::start(
         ///     "localhost:1491",
         ///     "SecretPassword",
-        /// )?;
+        /// ).await?;
         ///
-        /// let flushb_count = ingest_channel.flushb("search", "default")?;
+        /// let dest = Collection::new("search")?.bucket("default")?;
+        /// let flushb_count = ingest_channel.flushb(dest).await?;
         /// dbg!(flushb_count);
         /// # Ok(())
+        /// # })
         /// # }
+        /// # #[cfg(not(any(feature = "runtime-tokio", feature = "rt-async-io")))]
+        /// # fn main() {}
         /// ```
         use FlushCommand for fn flushb<'a>(
-            collection: &'a str,
-            bucket: &'a str => Some(bucket),
+            dest: Dest<'a> as target => dest.into(),
         );
     );
 
@@ -223,21 +279,27 @@ impl IngestChannel {
         ///
         /// ```rust,no_run
         /// # use sonic_channel::*;
+        /// # #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
         /// # fn main() -> result::Result<()> {
+        /// # futures_lite::future::block_on(async {
         /// let ingest_channel = IngestChannel::start(
         ///     "localhost:1491",
         ///     "SecretPassword",
-        /// )?;
+        /// ).await?;
         ///
-        /// let flusho_count = ingest_channel.flusho("search", "default", "recipe:296")?;
+        /// let dest = Collection::new("search")?
+        ///     .bucket("default")?
+        ///     .object("recipe:296")?;
+        /// let flusho_count = ingest_channel.flusho(dest).await?;
         /// dbg!(flusho_count);
         /// # Ok(())
+        /// # })
         /// # }
+        /// # #[cfg(not(any(feature = "runtime-tokio", feature = "rt-async-io")))]
+        /// # fn main() {}
         /// ```
         use FlushCommand for fn flusho<'a>(
-            collection: &'a str,
-            bucket: &'a str => Some(bucket),
-            object: &'a str => Some(object),
+            dest: ObjDest<'a> as target => dest.into(),
         );
     );
 
@@ -249,19 +311,25 @@ impl IngestChannel {
         ///
         /// ```rust,no_run
         /// # use sonic_channel::*;
+        /// # #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
         /// # fn main() -> result::Result<()> {
+        /// # futures_lite::future::block_on(async {
         /// let ingest_channel = IngestChannel::start(
         ///     "localhost:1491",
         ///     "SecretPassword",
-        /// )?;
+        /// ).await?;
         ///
-        /// let bucket_count = ingest_channel.bucket_count("search")?;
+        /// let collection = Collection::new("search")?;
+        /// let bucket_count = ingest_channel.bucket_count(collection).await?;
         /// dbg!(bucket_count);
         /// # Ok(())
+        /// # })
         /// # }
+        /// # #[cfg(not(any(feature = "runtime-tokio", feature = "rt-async-io")))]
+        /// # fn main() {}
         /// ```
         use CountCommand for fn bucket_count<'a>(
-            collection: &'a str,
+            collection: Collection<'a> as target => collection.into(),
         );
     );
 
@@ -273,20 +341,25 @@ impl IngestChannel {
         ///
         /// ```rust,no_run
         /// # use sonic_channel::*;
+        /// # #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
         /// # fn main() -> result::Result<()> {
+        /// # futures_lite::future::block_on(async {
         /// let ingest_channel = IngestChannel::start(
         ///     "localhost:1491",
         ///     "SecretPassword",
-        /// )?;
+        /// ).await?;
         ///
-        /// let object_count = ingest_channel.object_count("search", "default")?;
+        /// let dest = Collection::new("search")?.bucket("default")?;
+        /// let object_count = ingest_channel.object_count(dest).await?;
         /// dbg!(object_count);
         /// # Ok(())
+        /// # })
         /// # }
+        /// # #[cfg(not(any(feature = "runtime-tokio", feature = "rt-async-io")))]
+        /// # fn main() {}
         /// ```
         use CountCommand for fn object_count<'a>(
-            collection: &'a str,
-            bucket: &'a str => Some(bucket),
+            dest: Dest<'a> as target => dest.into(),
         );
     );
 
@@ -298,21 +371,99 @@ impl IngestChannel {
         ///
         /// ```rust,no_run
         /// # use sonic_channel::*;
+        /// # #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
         /// # fn main() -> result::Result<()> {
+        /// # futures_lite::future::block_on(async {
         /// let ingest_channel = IngestChannel::start(
         ///     "localhost:1491",
         ///     "SecretPassword",
-        /// )?;
+        /// ).await?;
         ///
-        /// let word_count = ingest_channel.word_count("search", "default", "recipe:296")?;
+        /// let dest = Collection::new("search")?
+        ///     .bucket("default")?
+        ///     .object("recipe:296")?;
+        /// let word_count = ingest_channel.word_count(dest).await?;
         /// dbg!(word_count);
         /// # Ok(())
+        /// # })
         /// # }
+        /// # #[cfg(not(any(feature = "runtime-tokio", feature = "rt-async-io")))]
+        /// # fn main() {}
         /// ```
         use CountCommand for fn word_count<'a>(
-            collection: &'a str,
-            bucket: &'a str => Some(bucket),
-            object: &'a str => Some(object),
+            dest: ObjDest<'a> as target => dest.into(),
+        );
+    );
+}
+
+impl<'a> Pipeline<'a, IngestChannel> {
+    init_command!(
+        /// Push search data in the index. Text is not split here, so
+        /// command that doesn't fit into the server buffer fails with
+        /// `ErrorKind::CommandTooLong`.
+        pipeline use PushCommand for fn push<'a>(
+            dest: ObjDest<'a>,
+            text: &'a str,
+        ) with { locale: None };
+    );
+
+    init_command!(
+        /// Push search data in the index with language of the text. Text is
+        /// not split here like in `push`.
+        pipeline use PushCommand for fn push_with_locale<'a>(
+            dest: ObjDest<'a>,
+            text: &'a str,
+            locale: Lang => Some(locale),
+        );
+    );
+
+    init_command!(
+        /// Pop search data from the index.
+        pipeline use PopCommand for fn pop<'a>(
+            dest: ObjDest<'a>,
+            text: &'a str,
+        );
+    );
+
+    init_command!(
+        /// Flush all indexed data from collections.
+        pipeline use FlushCommand for fn flushc<'a>(
+            collection: Collection<'a> as target => collection.into(),
+        );
+    );
+
+    init_command!(
+        /// Flush all indexed data from bucket in a collection.
+        pipeline use FlushCommand for fn flushb<'a>(
+            dest: Dest<'a> as target => dest.into(),
+        );
+    );
+
+    init_command!(
+        /// Flush all indexed data from an object in a bucket in collection.
+        pipeline use FlushCommand for fn flusho<'a>(
+            dest: ObjDest<'a> as target => dest.into(),
+        );
+    );
+
+    init_command!(
+        /// Bucket count in indexed search data of your collection.
+        pipeline use CountCommand for fn bucket_count<'a>(
+            collection: Collection<'a> as target => collection.into(),
+        );
+    );
+
+    init_command!(
+        /// Object count of bucket in indexed search data.
+        pipeline use CountCommand for fn object_count<'a>(
+            dest: Dest<'a> as target => dest.into(),
+        );
+    );
+
+    init_command!(
+        /// Object word count in indexed bucket search data.
+        pipeline use CountCommand for fn word_count<'a>(
+            dest: ObjDest<'a> as target => dest.into(),
         );
     );
 }
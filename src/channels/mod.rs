@@ -3,6 +3,17 @@ mod search;
 #[cfg(feature = "search")]
 pub use search::*;
 
+#[cfg(all(
+    feature = "search",
+    any(feature = "runtime-tokio", feature = "rt-async-io")
+))]
+mod multiplexed;
+#[cfg(all(
+    feature = "search",
+    any(feature = "runtime-tokio", feature = "rt-async-io")
+))]
+pub use multiplexed::MultiplexedSearchChannel;
+
 #[cfg(feature = "ingest")]
 mod ingest;
 #[cfg(feature = "ingest")]
@@ -13,22 +24,45 @@ mod control;
 #[cfg(feature = "control")]
 pub use control::*;
 
-use crate::commands::{StartCommand, StreamCommand};
+mod pipeline;
+pub use pipeline::{Pipeline, PipelineResponse};
+
+#[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
+use crate::commands::PingCommand;
+use crate::commands::{fit_buffer, HelpCommand, StartCommand, StreamCommand};
+use crate::options::ChannelOptions;
+use crate::protocol::{is_last_line, Response};
+#[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
+use crate::reconnect::Reconnect;
 use crate::result::*;
-use async_io::Async;
+use crate::server_info::{ServerInfo, ServerVersion};
+#[cfg(all(
+    feature = "tls",
+    any(feature = "runtime-tokio", feature = "rt-async-io")
+))]
+use crate::tls::TlsConfig;
+use crate::trace::{self, CommandSpan};
+use async_lock::Mutex;
 use async_trait::*;
-use futures_lite::{
-    io::{BufReader, BufWriter},
-    prelude::*,
-};
+use futures_lite::{io::BufReader, prelude::*};
 use std::fmt;
-use std::net::{SocketAddr, TcpStream};
-
-const DEFAULT_SONIC_PROTOCOL_VERSION: usize = 1;
-const UNINITIALIZED_MODE_MAX_BUFFER_SIZE: usize = 200;
+use std::io;
+#[cfg(any(
+    feature = "runtime-tokio",
+    feature = "rt-async-io",
+    feature = "blocking"
+))]
+use std::net::SocketAddr;
+#[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
+use std::net::ToSocketAddrs;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+#[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
+use std::sync::Weak;
+use std::time::{Duration, Instant};
 
 /// Channel modes supported by sonic search backend.
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ChannelMode {
     /// Sonic server search channel mode.
     ///
@@ -79,76 +113,574 @@ impl fmt::Display for ChannelMode {
     }
 }
 
+/// Orders resolved addresses so that IPv4 and IPv6 addresses alternate,
+/// starting with the family of the first address. If all addresses of one
+/// family are unreachable, connection falls back to the other family quickly.
+#[cfg(any(
+    feature = "runtime-tokio",
+    feature = "rt-async-io",
+    feature = "blocking"
+))]
+pub(crate) fn interleave_address_families<I>(addrs: I) -> Vec<SocketAddr>
+where
+    I: IntoIterator<Item = SocketAddr>,
+{
+    let addrs: Vec<SocketAddr> = addrs.into_iter().collect();
+    let prefer_v6 = addrs.first().is_some_and(SocketAddr::is_ipv6);
+    let (mut preferred, mut fallback): (Vec<_>, Vec<_>) = addrs
+        .into_iter()
+        .partition(|addr| addr.is_ipv6() == prefer_v6);
+
+    let mut result = Vec::with_capacity(preferred.len() + fallback.len());
+    let (mut preferred, mut fallback) = (preferred.drain(..), fallback.drain(..));
+    loop {
+        match (preferred.next(), fallback.next()) {
+            (None, None) => break,
+            (first, second) => result.extend(first.into_iter().chain(second)),
+        }
+    }
+    result
+}
+
+/// Byte stream that sonic channels can use to talk to the sonic server.
+///
+/// This trait is implemented for all types that implement `AsyncRead` and
+/// `AsyncWrite` from the `futures` crate, so you can run the sonic protocol over
+/// a Unix socket, a TLS stream or an in-memory pipe. Tokio types should be
+/// wrapped with `tokio_util::compat` first.
+pub trait Transport: AsyncRead + AsyncWrite + Unpin + Send + 'static {}
+
+impl<T> Transport for T where T: AsyncRead + AsyncWrite + Unpin + Send + 'static {}
+
+/// Transport with read buffer that lives as long as the connection. Server
+/// may send several responses in one segment or split one line into several
+/// segments, so bytes that are read ahead must wait for the next read.
+type Framed = BufReader<Box<dyn Transport>>;
+
+/// Connection to the sonic server. It's shared between the channel and its
+/// keepalive task.
+struct Connection {
+    stream: Mutex<Framed>,
+    options: ChannelOptions,
+    closed: AtomicBool,
+    running: AtomicUsize,
+    last_used: std::sync::Mutex<Instant>,
+}
+
+impl Connection {
+    fn new(io: Box<dyn Transport>, options: ChannelOptions) -> Self {
+        Connection {
+            stream: Mutex::new(BufReader::new(io)),
+            options,
+            closed: AtomicBool::new(false),
+            running: AtomicUsize::new(0),
+            last_used: std::sync::Mutex::new(Instant::now()),
+        }
+    }
+
+    async fn write_to(&self, stream: &mut Framed, message: &str) -> Result<()> {
+        let write = async {
+            stream.write_all(message.as_bytes()).await?;
+            stream.flush().await
+        };
+        self.guard(self.options.write_timeout, ErrorKind::WriteToStream, write)
+            .await
+    }
+
+    async fn read(&self, max_read_lines: usize) -> Result<String> {
+        let mut stream = self.stream.lock().await;
+        self.read_from(&mut stream, max_read_lines).await
+    }
+
+    async fn read_from(&self, stream: &mut Framed, max_read_lines: usize) -> Result<String> {
+        let read = async {
+            let mut message = String::new();
+
+            let mut lines_read = 0;
+            while lines_read < max_read_lines {
+                // futures-lite can read line only into an empty buffer.
+                let mut line = String::new();
+                if stream.read_line(&mut line).await? == 0 {
+                    return Err(io::ErrorKind::UnexpectedEof.into());
+                }
+                message.push_str(&line);
+                lines_read += 1;
+                if is_last_line(&line) {
+                    break;
+                }
+            }
+
+            Ok(message)
+        };
+        self.guard(self.options.read_timeout, ErrorKind::ReadStream, read)
+            .await
+    }
+
+    /// Runs IO operation with optional timeout. Stream is closed if the
+    /// operation fails, because we cannot know how many bytes were sent or
+    /// received and the next command would get the rest of this response.
+    async fn guard<T, F>(&self, timeout: Option<Duration>, kind: ErrorKind, io: F) -> Result<T>
+    where
+        F: Future<Output = io::Result<T>>,
+    {
+        match with_timeout(timeout, io).await {
+            Some(Ok(res)) => Ok(res),
+            Some(Err(err)) => {
+                self.closed.store(true, Ordering::SeqCst);
+                Err(Error::new(kind).with_source(err))
+            }
+            None => {
+                self.closed.store(true, Ordering::SeqCst);
+                Err(Error::new(ErrorKind::Timeout))
+            }
+        }
+    }
+
+    /// Writes command and reads response. Outer error means that IO failed,
+    /// inner one is the answer of the server. If the server answered `ENDED`
+    /// instead of running the command, it closes connection, so the stream
+    /// is closed too.
+    async fn send<SC: StreamCommand>(
+        &self,
+        command: &SC,
+        request: &str,
+    ) -> Result<Result<SC::Response>> {
+        // The lock is held for the whole command, so concurrent commands
+        // can't write their requests or read responses in between.
+        let mut stream = self.stream.lock().await;
+        let unfinished = Unfinished(&self.closed);
+        self.write_to(&mut stream, request).await?;
+        let message = self.read_from(&mut stream, SC::READ_LINES_COUNT).await?;
+        unfinished.finish();
+        drop(stream);
+        trace::record_response(message.len());
+
+        if message.starts_with("ENDED ") {
+            self.closed.store(true, Ordering::SeqCst);
+        }
+        Ok(command.receive(message))
+    }
+
+    /// Writes commands back-to-back in batches that fit into the server
+    /// buffer and reads their responses in the same order. Commands that
+    /// can't be encoded are not sent and get their error in place. If the
+    /// server answers `ENDED`, it closes connection, so commands after it get
+    /// `ErrorKind::ConnectionEnded` without reading. Outer error means that
+    /// IO failed and we cannot know which commands were run.
+    async fn send_pipeline(
+        &self,
+        commands: &[Box<dyn pipeline::Pipelined + Send + '_>],
+        mut requests: Vec<Result<String>>,
+        max_buffer_size: usize,
+    ) -> Result<Vec<Result<PipelineResponse>>> {
+        let mut stream = self.stream.lock().await;
+        let unfinished = Unfinished(&self.closed);
+
+        let mut responses = Vec::with_capacity(commands.len());
+        let mut received = 0;
+        let mut start = 0;
+        'batches: while start < commands.len() {
+            // Each request fits into the buffer, so the batch has at least
+            // one request if any is left.
+            let mut batch = String::new();
+            let mut end = start;
+            while let Some(request) = requests.get(end) {
+                if let Ok(request) = request {
+                    if !batch.is_empty() && batch.len() + request.len() > max_buffer_size {
+                        break;
+                    }
+                    batch.push_str(request);
+                }
+                end += 1;
+            }
+
+            if !batch.is_empty() {
+                self.write_to(&mut stream, &batch).await?;
+            }
+            for (command, request) in commands[start..end].iter().zip(&mut requests[start..end]) {
+                if let Err(err) = std::mem::replace(request, Ok(String::new())) {
+                    responses.push(Err(err));
+                    continue;
+                }
+
+                let message = self
+                    .read_from(&mut stream, command.read_lines_count())
+                    .await?;
+                received += message.len();
+                let ended = message
+                    .strip_prefix("ENDED ")
+                    .map(|reason| reason.trim_end().to_owned());
+                responses.push(command.receive(message));
+
+                if let Some(reason) = ended {
+                    self.closed.store(true, Ordering::SeqCst);
+                    while responses.len() < commands.len() {
+                        let kind = ErrorKind::ConnectionEnded(reason.clone());
+                        responses.push(Err(Error::new(kind)));
+                    }
+                    break 'batches;
+                }
+            }
+            start = end;
+        }
+
+        unfinished.finish();
+        trace::record_response(received);
+        Ok(responses)
+    }
+
+    /// Marks connection as busy until the returned guard is dropped.
+    fn busy(&self) -> Busy<'_> {
+        self.running.fetch_add(1, Ordering::SeqCst);
+        Busy(self)
+    }
+
+    /// Returns how long the connection has no running commands or `None`
+    /// if it's busy or closed.
+    #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
+    fn idle_for(&self) -> Option<Duration> {
+        if self.running.load(Ordering::SeqCst) > 0 || self.closed.load(Ordering::SeqCst) {
+            return None;
+        }
+        Some(self.last_used.lock().unwrap().elapsed())
+    }
+
+    /// Sends `PING` if no command is running. If `PONG` doesn't come in time,
+    /// connection is closed and the channel becomes unhealthy.
+    #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
+    async fn ping(&self, timeout: Duration) {
+        // Commands take the running counter before the stream lock, so
+        // nobody can write between our `PING` and `PONG` after this check.
+        let mut stream = self.stream.lock().await;
+        if self.idle_for().is_none() {
+            return;
+        }
+
+        let ping = async {
+            self.write_to(&mut stream, &PingCommand.message()?).await?;
+            let message = self.read_from(&mut stream, 1).await?;
+            PingCommand.receive(message)
+        };
+        match with_timeout(Some(timeout), ping).await {
+            Some(Ok(true)) => *self.last_used.lock().unwrap() = Instant::now(),
+            _ => self.closed.store(true, Ordering::SeqCst),
+        }
+    }
+}
+
+/// Closes connection if command is dropped before its response is read,
+/// because the response would be read by the next command.
+struct Unfinished<'a>(&'a AtomicBool);
+
+impl Unfinished<'_> {
+    fn finish(self) {
+        std::mem::forget(self);
+    }
+}
+
+impl Drop for Unfinished<'_> {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+struct Busy<'a>(&'a Connection);
+
+impl Drop for Busy<'_> {
+    fn drop(&mut self) {
+        *self.0.last_used.lock().unwrap() = Instant::now();
+        self.0.running.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Pings the server every time the connection is idle for the interval.
+/// Stops when the channel is dropped.
+#[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
+async fn keepalive(conn: Weak<Connection>, interval: Duration) {
+    let mut wait = interval;
+    loop {
+        crate::runtime::sleep(wait).await;
+        let conn = match conn.upgrade() {
+            Some(conn) => conn,
+            None => return,
+        };
+
+        wait = match conn.idle_for() {
+            Some(idle) if idle >= interval => {
+                conn.ping(interval).await;
+                interval
+            }
+            Some(idle) => interval - idle,
+            None => interval,
+        };
+    }
+}
+
 /// Root and Heart of this library.
 ///
 /// You can connect to the sonic search backend and run all supported protocol methods.
 ///
-#[derive(Debug)]
+/// Stream can be shared between tasks, for example in `Arc`. Commands are
+/// run one by one, so each command gets its own response.
+/// Use [`SonicPool`](crate::SonicPool) to run commands concurrently.
+///
 pub struct SonicStream {
-    stream: Async<TcpStream>,
-    mode: Option<ChannelMode>, // None – Uninitialized mode
-    max_buffer_size: usize,
-    protocol_version: usize,
+    conn: Arc<Connection>,
+    version: Option<ServerVersion>,
+    info: Option<ServerInfo>, // None – Uninitialized mode
+    #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
+    reconnect: Option<Reconnect>,
+}
+
+impl fmt::Debug for SonicStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SonicStream")
+            .field("version", &self.version)
+            .field("info", &self.info)
+            .field("options", &self.conn.options)
+            .field("closed", &self.conn.closed)
+            .finish()
+    }
 }
 
 impl SonicStream {
-    async fn write<SC: StreamCommand>(&self, command: &SC) -> Result<()> {
-        let mut writer = BufWriter::with_capacity(self.max_buffer_size, &self.stream);
-        let message = command.message();
-        dbg!(&message);
-        writer
-            .write_all(message.as_bytes())
-            .await
-            .map_err(|_| Error::new(ErrorKind::WriteToStream))?;
-        Ok(())
+    pub(crate) async fn run_command<SC: StreamCommand>(&self, command: SC) -> Result<SC::Response> {
+        let mut response = None;
+        for part in command.split(self.buffer_limit())? {
+            response = Some(self.run_part(part).await?);
+        }
+        response.ok_or_else(|| Error::new(ErrorKind::InvalidInput("Command has nothing to send")))
     }
 
-    async fn read(&self, max_read_lines: usize) -> Result<String> {
-        let mut reader = BufReader::with_capacity(self.max_buffer_size, &self.stream);
-        let mut message = String::new();
+    async fn run_part<SC: StreamCommand>(&self, command: SC) -> Result<SC::Response> {
+        let request = self.encode(command.name(), command.message())?;
+        let span = CommandSpan::new(self.mode(), &request, &self.conn.options);
+        let res = span.instrument(self.send_part(command, &request)).await;
+        span.finish(res)
+    }
 
-        let mut lines_read = 0;
-        while lines_read < max_read_lines {
-            reader
-                .read_line(&mut message)
-                .await
-                .map_err(|_| Error::new(ErrorKind::ReadStream))?;
-            lines_read += 1;
+    async fn send_part<SC: StreamCommand>(
+        &self,
+        command: SC,
+        request: &str,
+    ) -> Result<SC::Response> {
+        let _busy = self.conn.busy();
+
+        #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
+        if self.reconnect.is_some() {
+            return self.run_command_with_reconnect(command, request).await;
         }
 
-        Ok(message)
+        self.ensure_open().await?;
+        self.conn.send(&command, request).await?
     }
 
-    pub(crate) async fn run_command<SC: StreamCommand>(&self, command: SC) -> Result<SC::Response> {
-        self.write(&command).await?;
-        let message = self.read(SC::READ_LINES_COUNT).await?;
-        command.receive(message)
+    /// Returns mode of the channel, `None` before start.
+    fn mode(&self) -> Option<ChannelMode> {
+        self.info.as_ref().map(|info| info.mode)
+    }
+
+    /// Returns what the server told about itself on connect and start.
+    pub fn server_info(&self) -> &ServerInfo {
+        self.info
+            .as_ref()
+            .expect("Channels start the stream before giving it out")
+    }
+
+    /// Checks that the server supports the command and its line fits into
+    /// the server buffer, so the server won't reject it.
+    fn encode(&self, name: Option<&'static str>, message: Result<String>) -> Result<String> {
+        if let (Some(info), Some(name)) = (&self.info, name) {
+            if !info.supports(name) {
+                return Err(Error::new(ErrorKind::UnsupportedCommand((
+                    name,
+                    Some(info.mode),
+                ))));
+            }
+        }
+        fit_buffer(message?, self.buffer_limit())
+    }
+
+    /// Returns maximum length of the command line. The server announces it
+    /// on start, so commands before start are not limited.
+    fn buffer_limit(&self) -> usize {
+        match &self.info {
+            Some(info) => info.max_buffer_size,
+            None => usize::MAX,
+        }
+    }
+
+    pub(crate) async fn run_pipeline(
+        &self,
+        commands: &[Box<dyn pipeline::Pipelined + Send + '_>],
+    ) -> Result<Vec<Result<PipelineResponse>>> {
+        let _busy = self.conn.busy();
+        self.ensure_open().await?;
+        let requests = commands
+            .iter()
+            .map(|command| self.encode(command.name(), command.message()))
+            .collect();
+        let span = CommandSpan::pipeline(self.mode(), commands.len());
+        let res = span
+            .instrument(
+                self.conn
+                    .send_pipeline(commands, requests, self.buffer_limit()),
+            )
+            .await;
+        span.finish(res)
+    }
+
+    /// Reconnects if connection was lost and it's allowed by options.
+    async fn ensure_open(&self) -> Result<()> {
+        if !self.conn.closed.load(Ordering::SeqCst) {
+            return Ok(());
+        }
+
+        #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
+        if let Some(reconnect) = self.reconnect.as_ref() {
+            return self.reopen(reconnect).await;
+        }
+
+        Err(Error::new(ErrorKind::WriteToStream))
+    }
+
+    /// Reconnects before the command if connection was lost. If connection
+    /// is lost on this command, sends it once again when it's safe: the server
+    /// answered `ENDED` without running it, or the command is idempotent.
+    #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
+    async fn run_command_with_reconnect<SC: StreamCommand>(
+        &self,
+        command: SC,
+        request: &str,
+    ) -> Result<SC::Response> {
+        let mut retried = false;
+        loop {
+            self.ensure_open().await?;
+
+            let (err, safe) = match self.conn.send(&command, request).await {
+                Ok(Ok(response)) => return Ok(response),
+                Ok(Err(err)) if self.conn.closed.load(Ordering::SeqCst) => (err, true),
+                Err(err)
+                    if matches!(err.kind, ErrorKind::WriteToStream | ErrorKind::ReadStream) =>
+                {
+                    (err, SC::IDEMPOTENT)
+                }
+                Ok(Err(err)) | Err(err) => return Err(err),
+            };
+
+            if retried || !safe {
+                return Err(err);
+            }
+            retried = true;
+        }
+    }
+
+    /// Opens new connection instead of the lost one. Commands that come
+    /// meanwhile wait for the stream lock and use the new connection.
+    #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
+    async fn reopen(&self, reconnect: &Reconnect) -> Result<()> {
+        let mut stream = self.conn.stream.lock().await;
+        if !self.conn.closed.load(Ordering::SeqCst) {
+            return Ok(());
+        }
+
+        let mut attempt = 0;
+        loop {
+            crate::runtime::sleep(reconnect.policy.delay(attempt)).await;
+            match self.handshake(reconnect).await {
+                Ok(new_stream) => {
+                    *stream = new_stream;
+                    self.conn.closed.store(false, Ordering::SeqCst);
+                    return Ok(());
+                }
+                Err(err) if attempt + 1 >= reconnect.policy.attempts() => return Err(err),
+                Err(_) => attempt += 1,
+            }
+        }
+    }
+
+    /// Connects to the server and runs start command in the mode of this
+    /// stream. Sonic can't switch mode of connection, so the server must
+    /// accept the same mode again.
+    #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
+    async fn handshake(&self, reconnect: &Reconnect) -> Result<Framed> {
+        let mode = self
+            .mode()
+            .ok_or_else(|| Error::new(ErrorKind::ConnectToServer))?;
+        let mut stream = BufReader::new((reconnect.connect)().await?);
+
+        let message = self.conn.read_from(&mut stream, 1).await?;
+        if !message.starts_with("CONNECTED") {
+            return Err(Error::new(ErrorKind::ConnectToServer));
+        }
+
+        let command = StartCommand {
+            mode,
+            password: reconnect.password.clone(),
+        };
+        self.conn.write_to(&mut stream, &command.message()?).await?;
+        let message = self
+            .conn
+            .read_from(&mut stream, StartCommand::READ_LINES_COUNT)
+            .await?;
+        command.receive(message)?;
+
+        Ok(stream)
+    }
+
+    pub(crate) fn is_healthy(&self) -> bool {
+        !self.conn.closed.load(Ordering::SeqCst)
     }
 
-    async fn connect<A: Into<SocketAddr>>(addr: A) -> Result<Self> {
-        let stream = <Async<TcpStream>>::connect(addr)
+    /// Starts keepalive task if it's enabled by options.
+    fn spawn_keepalive(&self) {
+        #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
+        if let Some(interval) = self.conn.options.keepalive {
+            crate::runtime::spawn(keepalive(Arc::downgrade(&self.conn), interval));
+        }
+    }
+
+    #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
+    async fn open<A>(addr: A, options: &ChannelOptions) -> Result<Box<dyn Transport>>
+    where
+        A: ToSocketAddrs + Send + 'static,
+    {
+        #[cfg(feature = "tls")]
+        if let Some(config) = options.tls.as_ref() {
+            let stream = crate::tls::connect(addr, config, options.connect_timeout).await?;
+            return Ok(Box::new(stream));
+        }
+
+        let (stream, _) = crate::runtime::connect_tcp(addr, options.connect_timeout)
             .await
-            .map_err(|_| Error::new(ErrorKind::ConnectToServer))?;
+            .map_err(|err| Error::new(ErrorKind::ConnectToServer).with_source(err))?;
+        Ok(Box::new(stream))
+    }
 
+    async fn connect_with_transport(
+        io: Box<dyn Transport>,
+        options: ChannelOptions,
+    ) -> Result<Self> {
         let channel = SonicStream {
-            stream,
-            mode: None,
-            max_buffer_size: UNINITIALIZED_MODE_MAX_BUFFER_SIZE,
-            protocol_version: DEFAULT_SONIC_PROTOCOL_VERSION,
+            conn: Arc::new(Connection::new(io, options)),
+            version: None,
+            info: None,
+            #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
+            reconnect: None,
         };
 
-        let message = channel.read(1).await?;
-        dbg!(&message);
-        // TODO: need to add support for versions
-        if message.starts_with("CONNECTED") {
-            Ok(channel)
-        } else {
-            Err(Error::new(ErrorKind::ConnectToServer))
+        let message = channel.conn.read(1).await?;
+        match Response::parse(&message) {
+            Ok(Response::Connected(banner)) => Ok(SonicStream {
+                version: ServerVersion::from_banner(banner),
+                ..channel
+            }),
+            _ => Err(Error::new(ErrorKind::ConnectToServer)),
         }
     }
 
     async fn start<S: ToString>(&mut self, mode: ChannelMode, password: S) -> Result<()> {
-        if self.mode.is_some() {
+        if self.info.is_some() {
             return Err(Error::new(ErrorKind::RunCommand));
         }
 
@@ -158,9 +690,23 @@ impl SonicStream {
         };
         let response = self.run_command(command).await?;
 
-        self.max_buffer_size = response.max_buffer_size;
-        self.protocol_version = response.protocol_version;
-        self.mode = Some(response.mode);
+        self.info = Some(ServerInfo {
+            version: self.version,
+            protocol_version: response.protocol_version,
+            max_buffer_size: response.max_buffer_size,
+            mode: response.mode,
+            commands: None,
+        });
+
+        // Servers that can't answer `HELP` are checked by version.
+        let commands = match self.run_command(HelpCommand).await {
+            Ok(commands) => Some(commands),
+            Err(err) if err.is_connection_error() || err.is_timeout() => return Err(err),
+            Err(_) => None,
+        };
+        if let Some(info) = self.info.as_mut() {
+            info.commands = commands;
+        }
 
         Ok(())
     }
@@ -173,33 +719,82 @@ impl SonicStream {
     /// ```rust,no_run
     /// use sonic_channel::*;
     ///
+    /// # #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
     /// fn main() -> result::Result<()> {
-    ///     let channel = SearchChannel::start(
-    ///         "localhost:1491",
-    ///         "SecretPassword"
-    ///     )?;
+    ///     futures_lite::future::block_on(async {
+    ///         let channel = SearchChannel::start(
+    ///             "localhost:1491",
+    ///             "SecretPassword"
+    ///         ).await?;
     ///
-    ///     // Now you can use all method of Search channel.
-    ///     let objects = channel.query("search", "default", "beef");
+    ///         // Now you can use all method of Search channel.
+    ///         let dest = Collection::new("search")?.bucket("default")?;
+    ///         let objects = channel.query(QueryRequest::new(dest, "beef")).await;
     ///
-    ///     Ok(())
+    ///         Ok(())
+    ///     })
     /// }
+    /// # #[cfg(not(any(feature = "runtime-tokio", feature = "rt-async-io")))]
+    /// # fn main() {}
     /// ```
+    #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
     pub(crate) async fn connect_with_start<A, S>(
         mode: ChannelMode,
         addr: A,
         password: S,
+        options: ChannelOptions,
+    ) -> Result<Self>
+    where
+        A: ToSocketAddrs + Clone + Send + Sync + 'static,
+        S: ToString,
+    {
+        let stream = Self::open(addr.clone(), &options).await?;
+        let mut channel = Self::connect_with_transport(stream, options).await?;
+        channel.start(mode, password.to_string()).await?;
+
+        if let Some(policy) = channel.conn.options.reconnect.clone() {
+            let options = channel.conn.options.clone();
+            channel.reconnect = Some(Reconnect {
+                connect: Box::new(move || {
+                    let (addr, options) = (addr.clone(), options.clone());
+                    Box::pin(async move { Self::open(addr, &options).await })
+                }),
+                password: password.to_string(),
+                policy,
+            });
+        }
+
+        channel.spawn_keepalive();
+        Ok(channel)
+    }
+
+    pub(crate) async fn start_with_transport<T, S>(
+        mode: ChannelMode,
+        io: T,
+        password: S,
+        options: ChannelOptions,
     ) -> Result<Self>
     where
-        A: Into<SocketAddr>,
+        T: Transport,
         S: ToString,
     {
-        let mut channel = Self::connect(addr).await?;
+        let mut channel = Self::connect_with_transport(Box::new(io), options).await?;
         channel.start(mode, password).await?;
+        channel.spawn_keepalive();
         Ok(channel)
     }
 }
 
+/// Awaits future no longer than timeout. Returns `None` if time is out.
+/// Without any runtime there is no timer, so timeout is ignored.
+async fn with_timeout<F: Future>(timeout: Option<Duration>, future: F) -> Option<F::Output> {
+    match timeout {
+        #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
+        Some(timeout) => crate::runtime::timeout(timeout, future).await,
+        _ => Some(future.await),
+    }
+}
+
 #[async_trait]
 /// This trait should be implemented for all supported sonic channels
 pub trait SonicChannel {
@@ -209,20 +804,727 @@ pub trait SonicChannel {
     /// Returns reference for sonic stream of connection
     fn stream(&self) -> &SonicStream;
 
+    /// Returns `false` if connection was lost, timed out or keepalive ping
+    /// failed. Unhealthy channel reconnects on the next command if it's
+    /// allowed by options, otherwise all commands fail.
+    fn is_healthy(&self) -> bool {
+        self.stream().is_healthy()
+    }
+
+    /// Returns version of the server, buffer size, mode and commands that
+    /// the server supports. Unsupported commands fail with
+    /// `ErrorKind::UnsupportedCommand` before they are sent.
+    ///
+    /// ```rust,no_run
+    /// # use sonic_channel::*;
+    /// # #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
+    /// # fn main() -> result::Result<()> {
+    /// # futures_lite::future::block_on(async {
+    /// let search_channel = SearchChannel::start(
+    ///     "localhost:1491",
+    ///     "SecretPassword",
+    /// ).await?;
+    ///
+    /// let info = search_channel.server_info();
+    /// if info.supports("LIST") {
+    ///     dbg!(info.version, info.max_buffer_size);
+    /// }
+    /// # Ok(())
+    /// # })
+    /// # }
+    /// # #[cfg(not(any(feature = "runtime-tokio", feature = "rt-async-io")))]
+    /// # fn main() {}
+    /// ```
+    fn server_info(&self) -> &ServerInfo {
+        self.stream().server_info()
+    }
+
+    /// Creates empty pipeline of commands that are sent together and
+    /// answered in order. See [`Pipeline`] for details.
+    fn pipeline(&self) -> Pipeline<'_, Self>
+    where
+        Self: Sized,
+    {
+        Pipeline::new(self)
+    }
+
     /// Connects to sonic backend and run start command.
     ///
+    /// Address can be anything resolvable: `"host:port"` string, `(host, port)`
+    /// tuple or socket address. Resolved addresses are tried one by one until
+    /// the first successful connection.
+    ///
+    /// Note: This method requires enabling one of the `runtime-tokio`,
+    /// `runtime-async-std` or `runtime-smol` features.
+    ///
     /// ```rust,no_run
     /// # use sonic_channel::*;
+    /// # #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
     /// # fn main() -> result::Result<()> {
+    /// # futures_lite::future::block_on(async {
     /// let search_channel = SearchChannel::start(
     ///     "localhost:1491",
     ///     "SecretPassword",
-    /// )?;
+    /// ).await?;
     /// # Ok(())
+    /// # })
     /// # }
+    /// # #[cfg(not(any(feature = "runtime-tokio", feature = "rt-async-io")))]
+    /// # fn main() {}
     /// ```
+    #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
     async fn start<A, S>(addr: A, password: S) -> Result<Self::Channel>
     where
-        A: Into<SocketAddr> + Send + 'static,
+        A: ToSocketAddrs + Clone + Send + Sync + 'static,
+        S: ToString + Send + 'static,
+    {
+        Self::start_with_options(addr, password, ChannelOptions::default()).await
+    }
+
+    /// Connects to sonic backend with connection options and run start command.
+    ///
+    /// Note: This method requires enabling one of the `runtime-tokio`,
+    /// `runtime-async-std` or `runtime-smol` features.
+    ///
+    /// ```rust,no_run
+    /// # use sonic_channel::*;
+    /// # use std::time::Duration;
+    /// # fn main() -> result::Result<()> {
+    /// # futures_lite::future::block_on(async {
+    /// let search_channel = SearchChannel::start_with_options(
+    ///     "localhost:1491",
+    ///     "SecretPassword",
+    ///     ChannelOptions::new().read_timeout(Duration::from_secs(5)),
+    /// ).await?;
+    /// # Ok(())
+    /// # })
+    /// # }
+    /// ```
+    #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
+    async fn start_with_options<A, S>(
+        addr: A,
+        password: S,
+        options: ChannelOptions,
+    ) -> Result<Self::Channel>
+    where
+        A: ToSocketAddrs + Clone + Send + Sync + 'static,
+        S: ToString + Send + 'static;
+
+    /// Runs start command over already opened transport. Use it if you need
+    /// to connect to sonic over anything else than plain TCP.
+    ///
+    /// ```rust,no_run
+    /// # use sonic_channel::*;
+    /// # async fn run(io: impl Transport) -> result::Result<()> {
+    /// // `io` is an opened Unix socket, TLS stream or any other transport.
+    /// let search_channel = SearchChannel::start_with_transport(
+    ///     io,
+    ///     "SecretPassword",
+    /// ).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    async fn start_with_transport<T, S>(io: T, password: S) -> Result<Self::Channel>
+    where
+        T: Transport,
+        S: ToString + Send + 'static,
+    {
+        Self::start_with_transport_and_options(io, password, ChannelOptions::default()).await
+    }
+
+    /// Runs start command over already opened transport with connection
+    /// options. Connect timeout and TLS config are ignored here.
+    async fn start_with_transport_and_options<T, S>(
+        io: T,
+        password: S,
+        options: ChannelOptions,
+    ) -> Result<Self::Channel>
+    where
+        T: Transport,
         S: ToString + Send + 'static;
+
+    /// Connects to sonic backend over TLS and run start command.
+    ///
+    /// Note: This method requires enabling the `tls` feature and one of the
+    /// runtime features.
+    ///
+    /// ```rust,no_run
+    /// # use sonic_channel::*;
+    /// # fn main() -> result::Result<()> {
+    /// # futures_lite::future::block_on(async {
+    /// let ca = std::fs::read("ca.pem").unwrap();
+    /// let config = TlsConfig::without_roots()
+    ///     .add_root_certificates_pem(&ca)?
+    ///     .server_name("sonic.internal");
+    ///
+    /// let search_channel = SearchChannel::start_tls(
+    ///     "localhost:1491",
+    ///     "SecretPassword",
+    ///     config,
+    /// ).await?;
+    /// # Ok(())
+    /// # })
+    /// # }
+    /// ```
+    #[cfg(all(
+        feature = "tls",
+        any(feature = "runtime-tokio", feature = "rt-async-io")
+    ))]
+    async fn start_tls<A, S>(addr: A, password: S, config: TlsConfig) -> Result<Self::Channel>
+    where
+        A: ToSocketAddrs + Clone + Send + Sync + 'static,
+        S: ToString + Send + 'static,
+    {
+        Self::start_with_options(addr, password, ChannelOptions::new().tls(config)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(any(
+        feature = "runtime-tokio",
+        feature = "rt-async-io",
+        feature = "blocking"
+    ))]
+    fn interleave_resolved_addresses() {
+        let v4: SocketAddr = "127.0.0.1:1491".parse().unwrap();
+        let v4_2: SocketAddr = "127.0.0.2:1491".parse().unwrap();
+        let v6: SocketAddr = "[::1]:1491".parse().unwrap();
+
+        assert_eq!(
+            interleave_address_families(vec![v6, v4, v4_2]),
+            vec![v6, v4, v4_2]
+        );
+        assert_eq!(
+            interleave_address_families(vec![v4, v4_2, v6]),
+            vec![v4, v6, v4_2]
+        );
+        assert_eq!(interleave_address_families(vec![]), vec![]);
+    }
+
+    #[test]
+    fn start_with_in_memory_transport() {
+        use crate::commands::PingCommand;
+        use crate::test_utils::{any_mode, help_answer, MemoryTransport};
+        use futures_lite::future::block_on;
+
+        let mode = any_mode();
+        let responses = format!(
+            "CONNECTED <sonic-server v1.4.0-beta>\r\n\
+             STARTED {} protocol(1) buffer(20000)\r\n\
+             {}\
+             PONG\r\n",
+            mode,
+            help_answer(mode.to_str())
+        );
+        let io = MemoryTransport::new(responses.as_bytes());
+        let requests = io.requests();
+
+        let stream = block_on(SonicStream::start_with_transport(
+            mode,
+            io,
+            "secret",
+            ChannelOptions::default(),
+        ))
+        .unwrap();
+        let info = stream.server_info();
+        assert_eq!(info.version, Some(ServerVersion::new(1, 4, 0)));
+        assert_eq!(info.mode, mode);
+        assert_eq!(info.max_buffer_size, 20000);
+        assert!(info.supports("PING"));
+        assert!(!info.supports("SHUTDOWN"));
+
+        let pong = block_on(stream.run_command(PingCommand)).unwrap();
+        assert!(pong);
+        assert_eq!(
+            String::from_utf8(requests.lock().unwrap().clone()).unwrap(),
+            format!("START {} secret\r\nHELP commands\r\nPING\r\n", mode)
+        );
+    }
+
+    #[test]
+    #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
+    fn close_stream_after_read_timeout() {
+        use crate::commands::PingCommand;
+        use crate::runtime::block_on;
+        use crate::test_utils::{any_mode, hanging_sonic_server};
+
+        let addr = hanging_sonic_server(1);
+        let options = ChannelOptions::new().read_timeout(Duration::from_millis(100));
+
+        block_on(async {
+            let stream = SonicStream::connect_with_start(any_mode(), addr, "secret", options)
+                .await
+                .unwrap();
+
+            assert!(stream.run_command(PingCommand).await.unwrap());
+
+            let err = stream.run_command(PingCommand).await.unwrap_err();
+            assert!(matches!(err.kind, ErrorKind::Timeout));
+            assert!(err.is_timeout() && !err.is_connection_error());
+
+            let err = stream.run_command(PingCommand).await.unwrap_err();
+            assert!(matches!(err.kind, ErrorKind::WriteToStream));
+        });
+    }
+
+    #[test]
+    #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
+    fn reconnect_after_connection_loss() {
+        use crate::commands::PingCommand;
+        use crate::reconnect::ReconnectPolicy;
+        use crate::runtime::block_on;
+        use crate::test_utils::{any_mode, scripted_sonic_server};
+
+        let addr = scripted_sonic_server(vec![
+            vec![],
+            vec!["PONG\r\n", "ENDED timeout\r\n"],
+            vec!["PONG\r\n"],
+        ]);
+        let policy = ReconnectPolicy::new().initial_delay(Duration::from_millis(10));
+        let options = ChannelOptions::new().reconnect(policy);
+
+        block_on(async {
+            let stream = SonicStream::connect_with_start(any_mode(), addr, "secret", options)
+                .await
+                .unwrap();
+
+            // The first connection is dropped without response.
+            assert!(stream.run_command(PingCommand).await.unwrap());
+            // The second connection is ended by the server instead of response.
+            assert!(stream.run_command(PingCommand).await.unwrap());
+        });
+    }
+
+    #[test]
+    #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
+    fn mark_unhealthy_if_keepalive_ping_fails() {
+        use crate::runtime::{block_on, sleep};
+        use crate::test_utils::{any_mode, hanging_sonic_server};
+
+        // The first keepalive ping gets `PONG`, the second one hangs.
+        let addr = hanging_sonic_server(1);
+        let options = ChannelOptions::new().keepalive(Duration::from_millis(20));
+
+        block_on(async {
+            let stream = SonicStream::connect_with_start(any_mode(), addr, "secret", options)
+                .await
+                .unwrap();
+            assert!(stream.is_healthy());
+
+            sleep(Duration::from_millis(500)).await;
+            assert!(!stream.is_healthy());
+        });
+    }
+
+    #[test]
+    #[cfg(all(
+        feature = "search",
+        any(feature = "runtime-tokio", feature = "rt-async-io")
+    ))]
+    fn serialize_concurrent_commands() {
+        use crate::commands::{QueryCommand, QueryRequest};
+        use crate::ident::Collection;
+        use crate::runtime::block_on;
+        use crate::test_utils::ponging_sonic_server;
+        use futures_lite::future::zip;
+
+        let addr = ponging_sonic_server();
+
+        block_on(async {
+            let stream = SonicStream::connect_with_start(
+                ChannelMode::Search,
+                addr,
+                "secret",
+                ChannelOptions::default(),
+            )
+            .await
+            .unwrap();
+
+            let dest = Collection::new("collection")
+                .unwrap()
+                .bucket("bucket")
+                .unwrap();
+            let query = |terms| {
+                stream.run_command(QueryCommand {
+                    req: QueryRequest::new(dest, terms),
+                })
+            };
+            let (first, second) = zip(query("first"), query("second")).await;
+            assert_eq!(first.unwrap(), vec!["first"]);
+            assert_eq!(second.unwrap(), vec!["second"]);
+        });
+    }
+
+    #[test]
+    #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
+    fn close_stream_if_command_is_dropped() {
+        use crate::commands::PingCommand;
+        use crate::runtime::block_on;
+        use crate::test_utils::{any_mode, hanging_sonic_server};
+        use futures_lite::future::poll_once;
+
+        let addr = hanging_sonic_server(0);
+
+        block_on(async {
+            let stream = SonicStream::connect_with_start(
+                any_mode(),
+                addr,
+                "secret",
+                ChannelOptions::default(),
+            )
+            .await
+            .unwrap();
+
+            // Response of the dropped command would go to the next command.
+            assert!(poll_once(stream.run_command(PingCommand)).await.is_none());
+            assert!(!stream.is_healthy());
+        });
+    }
+
+    #[test]
+    fn keep_read_ahead_bytes_between_commands() {
+        use crate::commands::PingCommand;
+        use crate::test_utils::{any_mode, help_answer, MemoryTransport};
+        use futures_lite::future::block_on;
+
+        let mode = any_mode();
+        let responses = format!(
+            "CONNECTED <sonic-server v1.4.0>\r\n\
+             STARTED {} protocol(1) buffer(20000)\r\n\
+             {}\
+             PONG\r\n\
+             PONG\r\n\
+             PONG\r\n",
+            mode,
+            help_answer(mode.to_str())
+        );
+
+        for chunk_size in [1, 3, 16, usize::MAX] {
+            let io = MemoryTransport::chunked(responses.as_bytes(), chunk_size);
+            block_on(async {
+                let stream = SonicStream::start_with_transport(
+                    mode,
+                    io,
+                    "secret",
+                    ChannelOptions::default(),
+                )
+                .await
+                .unwrap();
+                for _ in 0..3 {
+                    assert!(stream.run_command(PingCommand).await.unwrap());
+                }
+            });
+        }
+    }
+
+    #[test]
+    fn pipeline_commands_within_buffer_size() {
+        use crate::commands::PingCommand;
+        use crate::test_utils::{any_mode, help_answer, MemoryTransport};
+        use futures_lite::future::block_on;
+
+        let mode = any_mode();
+        let responses = format!(
+            "CONNECTED <sonic-server v1.4.0>\r\n\
+             STARTED {} protocol(1) buffer(20)\r\n\
+             {}\
+             PONG\r\n\
+             PONG\r\n\
+             ERR unknown\r\n\
+             PONG\r\n\
+             PONG\r\n\
+             PONG\r\n\
+             PONG\r\n",
+            mode,
+            help_answer(mode.to_str())
+        );
+        let io = MemoryTransport::new(responses.as_bytes());
+        let writes = io.writes();
+
+        let stream = block_on(SonicStream::start_with_transport(
+            mode,
+            io,
+            "secret",
+            ChannelOptions::default(),
+        ))
+        .unwrap();
+        let commands: Vec<Box<dyn pipeline::Pipelined + Send>> =
+            (0..7).map(|_| Box::new(PingCommand) as _).collect();
+        let responses = block_on(stream.run_pipeline(&commands)).unwrap();
+
+        assert_eq!(responses.len(), 7);
+        for (i, response) in responses.into_iter().enumerate() {
+            if i == 2 {
+                assert!(response.is_err());
+            } else {
+                assert_eq!(response.unwrap(), PipelineResponse::Done(true));
+            }
+        }
+        // "PING\r\n" is 6 bytes, so only 3 commands fit into one write.
+        assert_eq!(writes.lock().unwrap()[2..], [18, 18, 6]);
+        assert!(stream.is_healthy());
+    }
+
+    #[test]
+    fn keep_pipeline_responses_after_ended() {
+        use crate::commands::PingCommand;
+        use crate::test_utils::{any_mode, help_answer, MemoryTransport};
+        use futures_lite::future::block_on;
+
+        let mode = any_mode();
+        let responses = format!(
+            "CONNECTED <sonic-server v1.4.0>\r\n\
+             STARTED {} protocol(1) buffer(20000)\r\n\
+             {}\
+             PONG\r\n\
+             ENDED shutting_down\r\n",
+            mode,
+            help_answer(mode.to_str())
+        );
+        let io = MemoryTransport::new(responses.as_bytes());
+
+        let stream = block_on(SonicStream::start_with_transport(
+            mode,
+            io,
+            "secret",
+            ChannelOptions::default(),
+        ))
+        .unwrap();
+        let commands: Vec<Box<dyn pipeline::Pipelined + Send>> =
+            (0..4).map(|_| Box::new(PingCommand) as _).collect();
+        let responses = block_on(stream.run_pipeline(&commands)).unwrap();
+
+        assert_eq!(responses.len(), 4);
+        assert_eq!(
+            responses[0].as_ref().unwrap(),
+            &PipelineResponse::Done(true)
+        );
+        for response in &responses[1..] {
+            let err = response.as_ref().unwrap_err();
+            assert!(
+                matches!(err.kind, ErrorKind::ConnectionEnded(ref reason) if reason == "shutting_down")
+            );
+        }
+        assert!(!stream.is_healthy());
+    }
+
+    #[test]
+    #[cfg(feature = "ingest")]
+    fn split_push_by_buffer_size() {
+        use crate::commands::{PopCommand, PushCommand};
+        use crate::ident::Collection;
+        use crate::test_utils::MemoryTransport;
+        use futures_lite::future::block_on;
+
+        let responses = "CONNECTED <sonic-server v1.3.0>\r\n\
+                         STARTED ingest protocol(1) buffer(40)\r\n\
+                         ERR unknown_command(HELP)\r\n\
+                         OK\r\n\
+                         OK\r\n";
+        let io = MemoryTransport::new(responses.as_bytes());
+        let requests = io.requests();
+
+        let stream = block_on(SonicStream::start_with_transport(
+            ChannelMode::Ingest,
+            io,
+            "secret",
+            ChannelOptions::default(),
+        ))
+        .unwrap();
+        let dest = Collection::new("c")
+            .unwrap()
+            .bucket("b")
+            .unwrap()
+            .object("o")
+            .unwrap();
+
+        let pushed = block_on(stream.run_command(PushCommand {
+            dest,
+            text: "Sweet Teriyaki Beef Skewers with sesame",
+            locale: None,
+        }));
+        assert!(pushed.unwrap());
+
+        let err = block_on(stream.run_command(PopCommand {
+            dest,
+            text: "Sweet Teriyaki Beef Skewers with sesame",
+        }))
+        .unwrap_err();
+        assert!(matches!(
+            err.kind,
+            ErrorKind::CommandTooLong {
+                length: 53,
+                max_buffer_size: 40
+            }
+        ));
+
+        assert_eq!(
+            String::from_utf8(requests.lock().unwrap().clone()).unwrap(),
+            "START ingest secret\r\n\
+             HELP commands\r\n\
+             PUSH c b o \"Sweet Teriyaki Beef \"\r\n\
+             PUSH c b o \"Skewers with sesame\"\r\n"
+        );
+        assert!(stream.is_healthy());
+    }
+
+    #[test]
+    #[cfg(feature = "search")]
+    fn list_all_words_by_pages() {
+        use crate::ident::Collection;
+        use crate::test_utils::MemoryTransport;
+        use futures_lite::future::block_on;
+
+        let responses = "CONNECTED <sonic-server v1.4.0>\r\n\
+                         STARTED search protocol(1) buffer(20000)\r\n\
+                         RESULT commands(QUERY, SUGGEST, LIST, PING, HELP, QUIT)\r\n\
+                         PENDING a\r\n\
+                         EVENT LIST a beef pork\r\n\
+                         PENDING b\r\n\
+                         EVENT LIST b lamb\r\n";
+        let io = MemoryTransport::new(responses.as_bytes());
+        let requests = io.requests();
+
+        let channel = block_on(SearchChannel::start_with_transport(io, "secret")).unwrap();
+        let dest = Collection::new("c").unwrap().bucket("b").unwrap();
+        let words = block_on(channel.list_all(dest, 2)).unwrap();
+
+        assert_eq!(words, vec!["beef", "pork", "lamb"]);
+        assert_eq!(
+            String::from_utf8(requests.lock().unwrap().clone()).unwrap(),
+            "START search secret\r\n\
+             HELP commands\r\n\
+             LIST c b LIMIT(2)\r\n\
+             LIST c b LIMIT(2) OFFSET(2)\r\n"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "search")]
+    fn parse_server_errors() {
+        use crate::commands::QueryRequest;
+        use crate::ident::Collection;
+        use crate::result::ServerErrorCode;
+        use crate::test_utils::{help_answer, MemoryTransport};
+        use futures_lite::future::block_on;
+
+        let responses = format!(
+            "CONNECTED <sonic-server v1.4.0>\r\n\
+             STARTED search protocol(1) buffer(20000)\r\n\
+             {}\
+             ERR invalid_format(QUERY <collection> <bucket> \"<terms>\")\r\n\
+             ERR query_error\r\n\
+             PONG\r\n\
+             ENDED shutting_down\r\n",
+            help_answer("search")
+        );
+        let io = MemoryTransport::new(responses.as_bytes());
+
+        let channel = block_on(SearchChannel::start_with_transport(io, "secret")).unwrap();
+        let dest = Collection::new("c").unwrap().bucket("b").unwrap();
+
+        // `ERR` comes instead of both `PENDING` and `EVENT` lines.
+        let err = block_on(channel.query(QueryRequest::new(dest, "beef"))).unwrap_err();
+        assert!(matches!(
+            err.kind,
+            ErrorKind::Server {
+                code: ServerErrorCode::InvalidFormat,
+                detail: Some(ref detail),
+            } if detail == "QUERY <collection> <bucket> \"<terms>\""
+        ));
+        assert_eq!(
+            err.server_line(),
+            Some("ERR invalid_format(QUERY <collection> <bucket> \"<terms>\")")
+        );
+        assert!(!err.is_retryable());
+        let err = block_on(channel.query(QueryRequest::new(dest, "beef"))).unwrap_err();
+        assert!(matches!(
+            err.kind,
+            ErrorKind::Server {
+                code: ServerErrorCode::QueryError,
+                detail: None,
+            }
+        ));
+        assert!(block_on(channel.ping()).unwrap());
+
+        let err = block_on(channel.ping()).unwrap_err();
+        assert!(
+            matches!(err.kind, ErrorKind::ConnectionEnded(ref reason) if reason == "shutting_down")
+        );
+        assert!(err.is_connection_error() && err.is_retryable());
+        assert!(!channel.is_healthy());
+    }
+
+    #[test]
+    #[cfg(feature = "search")]
+    fn reject_commands_unsupported_by_server() {
+        use crate::commands::ListRequest;
+        use crate::ident::Collection;
+        use crate::test_utils::MemoryTransport;
+        use futures_lite::future::block_on;
+
+        // Old servers without `HELP` are checked by version.
+        let responses = "CONNECTED <sonic-server v1.3.0>\r\n\
+                         STARTED search protocol(1) buffer(20000)\r\n\
+                         ERR unknown_command(HELP)\r\n\
+                         PONG\r\n";
+        let io = MemoryTransport::new(responses.as_bytes());
+        let requests = io.requests();
+
+        let channel = block_on(SearchChannel::start_with_transport(io, "secret")).unwrap();
+        assert_eq!(channel.server_info().commands, None);
+
+        let dest = Collection::new("c").unwrap().bucket("b").unwrap();
+        let err = block_on(channel.list(ListRequest::new(dest))).unwrap_err();
+        assert!(matches!(
+            err.kind,
+            ErrorKind::UnsupportedCommand(("LIST", Some(ChannelMode::Search)))
+        ));
+        assert!(block_on(channel.ping()).unwrap());
+
+        assert_eq!(
+            String::from_utf8(requests.lock().unwrap().clone()).unwrap(),
+            "START search secret\r\n\
+             HELP commands\r\n\
+             PING\r\n"
+        );
+        assert!(channel.is_healthy());
+    }
+
+    #[test]
+    #[cfg(feature = "control")]
+    fn parse_server_stats() {
+        use crate::commands::ServerStats;
+        use crate::test_utils::MemoryTransport;
+        use futures_lite::future::block_on;
+
+        let responses = "CONNECTED <sonic-server v1.4.0>\r\n\
+                         STARTED control protocol(1) buffer(20000)\r\n\
+                         RESULT commands(TRIGGER, INFO, PING, HELP, QUIT)\r\n\
+                         RESULT uptime(24) clients_connected(2) commands_total(13) \
+                         command_latency_best(1) command_latency_worst(7) kv_open_count(1) \
+                         fst_open_count(0) fst_consolidate_count(3) from_the_future(x)\r\n";
+        let io = MemoryTransport::new(responses.as_bytes());
+
+        let channel = block_on(ControlChannel::start_with_transport(io, "secret")).unwrap();
+        let stats = block_on(channel.info()).unwrap();
+
+        assert_eq!(
+            stats,
+            ServerStats {
+                uptime: Duration::from_secs(24),
+                clients_connected: 2,
+                commands_total: 13,
+                command_latency_best: Duration::from_millis(1),
+                command_latency_worst: Duration::from_millis(7),
+                kv_open_count: 1,
+                fst_open_count: 0,
+                fst_consolidate_count: 3,
+            }
+        );
+    }
 }
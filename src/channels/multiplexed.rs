@@ -0,0 +1,364 @@
+use super::{with_timeout, ChannelMode, Framed, SonicStream};
+use crate::commands::*;
+use crate::options::ChannelOptions;
+use crate::result::*;
+use crate::server_info::ServerInfo;
+use crate::trace::{self, CommandSpan};
+use async_lock::Mutex;
+use futures_lite::io::{split, BufReader, ReadHalf, WriteHalf};
+use futures_lite::{future, prelude::*};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::net::ToSocketAddrs;
+use std::sync::Arc;
+
+/// Search channel that sends commands without waiting for responses of
+/// previous ones, so one connection can serve many concurrent callers.
+///
+/// Sonic answers `QUERY` and `SUGGEST` with `PENDING <id>` right away and
+/// sends `EVENT QUERY <id> ...` when the search is done. The channel reads
+/// all responses in background and routes each event to the command that
+/// waits for the id, so results may come in any order.
+///
+/// Read timeout limits waiting of one command and doesn't close the channel.
+/// If connection is lost, all waiting commands fail and the channel must be
+/// started again: reconnect policy and keepalive are ignored here.
+///
+/// **Note:** This type requires enabling the `search` feature and one of the
+/// runtime features.
+///
+/// ```rust,no_run
+/// # use sonic_channel::*;
+/// # fn main() -> result::Result<()> {
+/// # futures_lite::future::block_on(async {
+/// let channel = MultiplexedSearchChannel::start(
+///     "localhost:1491",
+///     "SecretPassword",
+/// ).await?;
+///
+/// let dest = Collection::new("recipes")?.bucket("default")?;
+/// let (beef, pork) = futures_lite::future::zip(
+///     channel.query(QueryRequest::new(dest, "beef")),
+///     channel.query(QueryRequest::new(dest, "pork")),
+/// ).await;
+/// dbg!(beef?, pork?);
+/// # Ok(())
+/// # })
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct MultiplexedSearchChannel(Multiplexer);
+
+impl MultiplexedSearchChannel {
+    /// Connects to sonic backend and run start command in search mode.
+    pub async fn start<A, S>(addr: A, password: S) -> Result<Self>
+    where
+        A: ToSocketAddrs + Send + 'static,
+        S: ToString,
+    {
+        Self::start_with_options(addr, password, ChannelOptions::default()).await
+    }
+
+    /// Connects to sonic backend with connection options and run start
+    /// command in search mode.
+    pub async fn start_with_options<A, S>(
+        addr: A,
+        password: S,
+        options: ChannelOptions,
+    ) -> Result<Self>
+    where
+        A: ToSocketAddrs + Send + 'static,
+        S: ToString,
+    {
+        let io = SonicStream::open(addr, &options).await?;
+        let mut stream = SonicStream::connect_with_transport(io, options).await?;
+        stream.start(ChannelMode::Search, password).await?;
+
+        // Nobody else holds the connection before keepalive is spawned.
+        let info = stream.server_info().clone();
+        let conn =
+            Arc::try_unwrap(stream.conn).map_err(|_| Error::new(ErrorKind::ConnectToServer))?;
+        Ok(Self(Multiplexer::new(
+            conn.stream.into_inner(),
+            conn.options,
+            info,
+        )))
+    }
+
+    fn stream(&self) -> &Multiplexer {
+        &self.0
+    }
+
+    /// Returns what the server told about itself on connect and start.
+    /// See [`SonicChannel::server_info`](crate::SonicChannel::server_info).
+    pub fn server_info(&self) -> &ServerInfo {
+        &self.0.info
+    }
+
+    init_command!(
+        /// Stop connection. Commands that wait for events fail.
+        use QuitCommand for fn quit();
+    );
+
+    init_command!(
+        /// Ping server.
+        use PingCommand for fn ping();
+    );
+
+    init_command!(
+        /// Query objects in database. See [`SearchChannel::query`](crate::SearchChannel::query).
+        use QueryCommand for fn query<'a>(
+            req: QueryRequest<'a>,
+        );
+    );
+
+    init_command!(
+        /// Suggest auto-completes words.
+        /// See [`SearchChannel::suggest`](crate::SearchChannel::suggest).
+        use SuggestCommand for fn suggest<'a>(
+            req: SuggestRequest<'a>,
+        );
+    );
+
+    init_command!(
+        /// List indexed words of the bucket.
+        /// See [`SearchChannel::list`](crate::SearchChannel::list).
+        use ListCommand for fn list<'a>(
+            req: ListRequest<'a>,
+        );
+    );
+}
+
+/// Sender of the full response of one command.
+type Reply = async_channel::Sender<String>;
+
+#[derive(Default)]
+struct Waiters {
+    /// Commands in order of writes that wait for the first line of response.
+    replies: VecDeque<Reply>,
+    /// Commands that got `PENDING <id>` and wait for the event with this id.
+    events: HashMap<String, (String, Reply)>,
+    /// Stops background reader when dropped.
+    shutdown: Option<async_channel::Sender<()>>,
+    closed: bool,
+}
+
+/// State shared with the background reader.
+struct Shared {
+    waiters: std::sync::Mutex<Waiters>,
+}
+
+impl Shared {
+    fn waiters(&self) -> std::sync::MutexGuard<'_, Waiters> {
+        self.waiters
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    /// Adds command to the queue. Must be called under the writer lock,
+    /// so the queue has the same order as requests.
+    fn register(&self, reply: Reply) -> Result<()> {
+        let mut waiters = self.waiters();
+        if waiters.closed {
+            return Err(Error::new(ErrorKind::WriteToStream));
+        }
+        waiters.replies.push_back(reply);
+        Ok(())
+    }
+
+    /// Sends the line to the command that waits for it. `PENDING` line is
+    /// kept until the event comes, so the command gets both lines at once.
+    fn dispatch(&self, line: String) {
+        let mut waiters = self.waiters();
+
+        if line.starts_with("EVENT ") {
+            let id = line.split_whitespace().nth(2).unwrap_or_default();
+            if let Some((pending, reply)) = waiters.events.remove(id) {
+                let _ = reply.try_send(pending + &line);
+            }
+            return;
+        }
+
+        let reply = match waiters.replies.pop_front() {
+            Some(reply) => reply,
+            None => return,
+        };
+        if line.starts_with("PENDING ") {
+            let id = line.split_whitespace().nth(1).unwrap_or_default();
+            waiters.events.insert(id.to_string(), (line, reply));
+            return;
+        }
+        if line.starts_with("ENDED ") {
+            waiters.closed = true;
+        }
+        let _ = reply.try_send(line);
+    }
+
+    /// Marks channel closed, fails all waiting commands and stops reader.
+    fn close(&self) {
+        let mut waiters = self.waiters();
+        waiters.closed = true;
+        waiters.replies.clear();
+        waiters.events.clear();
+        waiters.shutdown = None;
+    }
+}
+
+/// Closes channel if request was not written completely.
+struct Unfinished<'a>(&'a Shared);
+
+impl Unfinished<'_> {
+    fn finish(self) {
+        std::mem::forget(self);
+    }
+}
+
+impl Drop for Unfinished<'_> {
+    fn drop(&mut self) {
+        self.0.close();
+    }
+}
+
+struct Multiplexer {
+    writer: Mutex<WriteHalf<Framed>>,
+    shared: Arc<Shared>,
+    options: ChannelOptions,
+    info: ServerInfo,
+}
+
+impl fmt::Debug for Multiplexer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Multiplexer")
+            .field("closed", &self.shared.waiters().closed)
+            .field("options", &self.options)
+            .field("info", &self.info)
+            .finish()
+    }
+}
+
+impl Multiplexer {
+    fn new(stream: Framed, options: ChannelOptions, info: ServerInfo) -> Self {
+        let (reader, writer) = split(stream);
+        let (shutdown, stopped) = async_channel::bounded(1);
+        let shared = Arc::new(Shared {
+            waiters: std::sync::Mutex::new(Waiters {
+                shutdown: Some(shutdown),
+                ..Waiters::default()
+            }),
+        });
+
+        crate::runtime::spawn(read_responses(
+            shared.clone(),
+            BufReader::new(reader),
+            stopped,
+        ));
+
+        Multiplexer {
+            writer: Mutex::new(writer),
+            shared,
+            options,
+            info,
+        }
+    }
+
+    async fn run_command<SC: StreamCommand>(&self, command: SC) -> Result<SC::Response> {
+        if let Some(name) = command.name() {
+            if !self.info.supports(name) {
+                return Err(Error::new(ErrorKind::UnsupportedCommand((
+                    name,
+                    Some(self.info.mode),
+                ))));
+            }
+        }
+        let message = fit_buffer(command.message()?, self.info.max_buffer_size)?;
+        let span = CommandSpan::new(Some(self.info.mode), &message, &self.options);
+        let res = span.instrument(self.send(command, &message)).await;
+        span.finish(res)
+    }
+
+    async fn send<SC: StreamCommand>(&self, command: SC, message: &str) -> Result<SC::Response> {
+        let (reply, response) = async_channel::bounded(1);
+        {
+            let mut writer = self.writer.lock().await;
+            self.shared.register(reply)?;
+            let unfinished = Unfinished(&self.shared);
+            let write = async {
+                writer.write_all(message.as_bytes()).await?;
+                writer.flush().await
+            };
+            match with_timeout(self.options.write_timeout, write).await {
+                Some(Ok(())) => unfinished.finish(),
+                Some(Err(err)) => return Err(Error::new(ErrorKind::WriteToStream).with_source(err)),
+                None => return Err(Error::new(ErrorKind::Timeout)),
+            }
+        }
+
+        match with_timeout(self.options.read_timeout, response.recv()).await {
+            Some(Ok(message)) => {
+                trace::record_response(message.len());
+                command.receive(message)
+            }
+            Some(Err(err)) => Err(Error::new(ErrorKind::ReadStream).with_source(err)),
+            None => Err(Error::new(ErrorKind::Timeout)),
+        }
+    }
+}
+
+impl Drop for Multiplexer {
+    fn drop(&mut self) {
+        self.shared.close();
+    }
+}
+
+/// Reads responses until connection is lost or the channel is dropped.
+async fn read_responses(
+    shared: Arc<Shared>,
+    mut reader: BufReader<ReadHalf<Framed>>,
+    stopped: async_channel::Receiver<()>,
+) {
+    let mut line = String::new();
+    loop {
+        line.clear();
+        let read = future::or(async { Some(reader.read_line(&mut line).await) }, async {
+            let _ = stopped.recv().await;
+            None
+        })
+        .await;
+        match read {
+            Some(Ok(len)) if len > 0 => shared.dispatch(std::mem::take(&mut line)),
+            _ => break,
+        }
+    }
+    shared.close();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ident::Collection;
+    use crate::runtime::block_on;
+    use crate::test_utils::multiplexing_sonic_server;
+
+    #[test]
+    fn route_events_by_pending_id() {
+        let addr = multiplexing_sonic_server(3);
+        block_on(async {
+            let channel = MultiplexedSearchChannel::start(addr, "secret")
+                .await
+                .unwrap();
+
+            let dest = Collection::new("c").unwrap().bucket("b").unwrap();
+            let (first, (second, third)) = future::zip(
+                channel.query(QueryRequest::new(dest, "beef")),
+                future::zip(
+                    channel.query(QueryRequest::new(dest, "pork")),
+                    channel.query(QueryRequest::new(dest, "lamb chop")),
+                ),
+            )
+            .await;
+            assert_eq!(first.unwrap(), vec!["beef"]);
+            assert_eq!(second.unwrap(), vec!["pork"]);
+            assert_eq!(third.unwrap(), vec!["lamb", "chop"]);
+        });
+    }
+}
@@ -0,0 +1,163 @@
+use super::SonicChannel;
+use crate::commands::*;
+use crate::result::Result;
+
+/// Response of one pipelined command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PipelineResponse {
+    /// Command succeeded. Returned by `ping`, `push` and triggers.
+    Done(bool),
+    /// Count of words or objects. Returned by `pop`, `flush*` and `*_count`.
+    Count(usize),
+    /// Object ids or words. Returned by `query` and `suggest`.
+    List(Vec<String>),
+}
+
+impl From<bool> for PipelineResponse {
+    fn from(done: bool) -> Self {
+        PipelineResponse::Done(done)
+    }
+}
+
+impl From<usize> for PipelineResponse {
+    fn from(count: usize) -> Self {
+        PipelineResponse::Count(count)
+    }
+}
+
+impl From<Vec<String>> for PipelineResponse {
+    fn from(list: Vec<String>) -> Self {
+        PipelineResponse::List(list)
+    }
+}
+
+/// Command with erased response type, so commands of different kinds can be
+/// sent in one pipeline.
+pub(crate) trait Pipelined {
+    fn name(&self) -> Option<&'static str>;
+
+    fn message(&self) -> Result<String>;
+
+    fn read_lines_count(&self) -> usize;
+
+    fn receive(&self, message: String) -> Result<PipelineResponse>;
+}
+
+impl<SC> Pipelined for SC
+where
+    SC: StreamCommand,
+    SC::Response: Into<PipelineResponse>,
+{
+    fn name(&self) -> Option<&'static str> {
+        StreamCommand::name(self)
+    }
+
+    fn message(&self) -> Result<String> {
+        StreamCommand::message(self)
+    }
+
+    fn read_lines_count(&self) -> usize {
+        SC::READ_LINES_COUNT
+    }
+
+    fn receive(&self, message: String) -> Result<PipelineResponse> {
+        StreamCommand::receive(self, message).map(Into::into)
+    }
+}
+
+/// Batch of commands that are sent to the server without waiting for
+/// responses of previous commands.
+///
+/// Commands are written in as few writes as possible, but each write fits
+/// into the buffer size that was negotiated with the server. Responses are
+/// matched with commands in order, so one failed command doesn't affect
+/// others. If the server ends connection in the middle, commands after it
+/// get `ErrorKind::ConnectionEnded`.
+///
+/// Unlike `IngestChannel::push`, pipelined
+/// `push` doesn't split long text: command that doesn't fit into the buffer
+/// gets `ErrorKind::CommandTooLong` and isn't sent.
+///
+/// ```rust,no_run
+/// # use sonic_channel::*;
+/// # #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
+/// # fn main() -> result::Result<()> {
+/// # futures_lite::future::block_on(async {
+/// let search_channel = SearchChannel::start(
+///     "localhost:1491",
+///     "SecretPassword",
+/// ).await?;
+///
+/// let dest = Collection::new("search")?.bucket("default")?;
+/// let responses = search_channel
+///     .pipeline()
+///     .query(QueryRequest::new(dest, "beef"))
+///     .query(QueryRequest::new(dest, "teriyaki").limit(10))
+///     .suggest(SuggestRequest::new(dest, "bee"))
+///     .execute()
+///     .await?;
+/// assert_eq!(responses.len(), 3);
+/// # Ok(())
+/// # })
+/// # }
+/// # #[cfg(not(any(feature = "runtime-tokio", feature = "rt-async-io")))]
+/// # fn main() {}
+/// ```
+pub struct Pipeline<'a, C> {
+    channel: &'a C,
+    commands: Vec<Box<dyn Pipelined + Send + 'a>>,
+}
+
+impl<'a, C> std::fmt::Debug for Pipeline<'a, C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Pipeline")
+            .field("commands", &self.commands.len())
+            .finish()
+    }
+}
+
+impl<'a, C: SonicChannel> Pipeline<'a, C> {
+    pub(crate) fn new(channel: &'a C) -> Self {
+        Pipeline {
+            channel,
+            commands: Vec::new(),
+        }
+    }
+
+    pub(crate) fn add<SC>(mut self, command: SC) -> Self
+    where
+        SC: Pipelined + Send + 'a,
+    {
+        self.commands.push(Box::new(command));
+        self
+    }
+
+    /// Returns count of commands in the pipeline.
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    /// Returns `true` if there are no commands in the pipeline.
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    init_command!(
+        /// Ping server.
+        pipeline use PingCommand for fn ping();
+    );
+
+    /// Sends all commands and returns their responses in the same order.
+    ///
+    /// Outer error means that connection failed and results of the commands
+    /// are unknown. After such error the channel is closed. Connection
+    /// ended by the server is not such error, responses that were received
+    /// before it are returned.
+    pub async fn execute(self) -> Result<Vec<Result<PipelineResponse>>> {
+        if self.commands.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        self.channel.stream().run_pipeline(&self.commands).await
+    }
+}
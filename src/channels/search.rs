@@ -1,8 +1,10 @@
-use super::{ChannelMode, SonicChannel, SonicStream};
+use super::{ChannelMode, Pipeline, SonicChannel, SonicStream, Transport};
 use crate::commands::*;
+use crate::options::ChannelOptions;
 use crate::result::Result;
 use async_trait::*;
-use std::net::SocketAddr;
+#[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
+use std::net::ToSocketAddrs;
 
 /// The Sonic Channel Search mode is used for querying the search index.
 /// Once in this mode, you cannot switch to other modes or gain access
@@ -10,7 +12,7 @@ use std::net::SocketAddr;
 ///
 /// ### Available commands
 ///
-/// In this mode you can use `query`, `suggest`, `ping` and `quit` commands.
+/// In this mode you can use `query`, `suggest`, `list`, `ping` and `quit` commands.
 ///
 /// **Note:** This mode requires enabling the `search` feature.
 #[derive(Debug)]
@@ -24,12 +26,31 @@ impl SonicChannel for SearchChannel {
         &self.0
     }
 
-    async fn start<A, S>(addr: A, password: S) -> Result<Self::Channel>
+    #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
+    async fn start_with_options<A, S>(
+        addr: A,
+        password: S,
+        options: ChannelOptions,
+    ) -> Result<Self::Channel>
     where
-        A: Into<SocketAddr> + Send + 'static,
+        A: ToSocketAddrs + Clone + Send + Sync + 'static,
         S: ToString + Send + 'static,
     {
-        SonicStream::connect_with_start(ChannelMode::Search, addr, password)
+        SonicStream::connect_with_start(ChannelMode::Search, addr, password, options)
+            .await
+            .map(Self)
+    }
+
+    async fn start_with_transport_and_options<T, S>(
+        io: T,
+        password: S,
+        options: ChannelOptions,
+    ) -> Result<Self::Channel>
+    where
+        T: Transport,
+        S: ToString + Send + 'static,
+    {
+        SonicStream::start_with_transport(ChannelMode::Search, io, password, options)
             .await
             .map(Self)
     }
@@ -41,15 +62,21 @@ impl SearchChannel {
         ///
         /// ```rust,no_run
         /// # use sonic_channel::*;
+        /// # #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
         /// # fn main() -> result::Result<()> {
+        /// # futures_lite::future::block_on(async {
         /// let channel = SearchChannel::start(
         ///     "localhost:1491",
         ///     "SecretPassword",
-        /// )?;
+        /// ).await?;
         ///
-        /// channel.quit()?;
+        /// channel.quit().await?;
         /// # Ok(())
+        /// # })
         /// # }
+        /// # #[cfg(not(any(feature = "runtime-tokio", feature = "rt-async-io")))]
+        /// # fn main() {}
+        /// ```
         use QuitCommand for fn quit();
     );
 
@@ -58,164 +85,159 @@ impl SearchChannel {
         ///
         /// ```rust,no_run
         /// # use sonic_channel::*;
+        /// # #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
         /// # fn main() -> result::Result<()> {
+        /// # futures_lite::future::block_on(async {
         /// let channel = SearchChannel::start(
         ///     "localhost:1491",
         ///     "SecretPassword",
-        /// )?;
+        /// ).await?;
         ///
-        /// channel.ping()?;
+        /// channel.ping().await?;
         /// # Ok(())
+        /// # })
         /// # }
+        /// # #[cfg(not(any(feature = "runtime-tokio", feature = "rt-async-io")))]
+        /// # fn main() {}
+        /// ```
         use PingCommand for fn ping();
     );
 }
 
 impl SearchChannel {
     init_command!(
-        /// Query objects in database.
+        /// Query objects in database. Request sets the terms and optional
+        /// limit, offset and language, see [`QueryRequest`].
+        ///
+        /// Terms that don't fit into the server buffer are not sent, the
+        /// method fails with `ErrorKind::CommandTooLong`.
         ///
         /// Note: This method requires enabling the `search` feature and start
         /// connection in Search mode.
         ///
         /// ```rust,no_run
         /// # use sonic_channel::*;
+        /// # #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
         /// # fn main() -> result::Result<()> {
+        /// # futures_lite::future::block_on(async {
         /// let search_channel = SearchChannel::start(
         ///     "localhost:1491",
         ///     "SecretPassword",
-        /// )?;
+        /// ).await?;
         ///
-        /// let result = search_channel.query("search", "default", "Beef")?;
+        /// let dest = Collection::new("search")?.bucket("default")?;
+        /// let result = search_channel
+        ///     .query(QueryRequest::new(dest, "Beef").limit(10).lang(Lang::Eng)).await?;
         /// dbg!(result);
         /// # Ok(())
+        /// # })
         /// # }
+        /// # #[cfg(not(any(feature = "runtime-tokio", feature = "rt-async-io")))]
+        /// # fn main() {}
         /// ```
         use QueryCommand for fn query<'a>(
-            collection: &'a str,
-            bucket: &'a str,
-            terms: &'a str,
+            req: QueryRequest<'a>,
         );
     );
 
     init_command!(
-        /// Query limited objects in database. This method similar query but
-        /// you can configure limit of result.
+        /// Suggest auto-completes words. Request sets the word and optional
+        /// limit, see [`SuggestRequest`].
         ///
         /// Note: This method requires enabling the `search` feature and start
         /// connection in Search mode.
         ///
         /// ```rust,no_run
         /// # use sonic_channel::*;
+        /// # #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
         /// # fn main() -> result::Result<()> {
+        /// # futures_lite::future::block_on(async {
         /// let search_channel = SearchChannel::start(
         ///     "localhost:1491",
         ///     "SecretPassword",
-        /// )?;
+        /// ).await?;
         ///
-        /// let result = search_channel.query_with_limit(
-        ///     "search",
-        ///     "default",
-        ///     "Beef",
-        ///     10,
-        /// )?;
+        /// let dest = Collection::new("search")?.bucket("default")?;
+        /// let result = search_channel.suggest(SuggestRequest::new(dest, "Beef")).await?;
         /// dbg!(result);
         /// # Ok(())
+        /// # })
         /// # }
+        /// # #[cfg(not(any(feature = "runtime-tokio", feature = "rt-async-io")))]
+        /// # fn main() {}
         /// ```
-        use QueryCommand for fn query_with_limit<'a>(
-            collection: &'a str,
-            bucket: &'a str,
-            terms: &'a str,
-            limit: usize => Some(limit),
+        use SuggestCommand for fn suggest<'a>(
+            req: SuggestRequest<'a>,
         );
     );
 
     init_command!(
-        /// Query limited objects in database. This method similar
-        /// query_with_limit but you can put offset in your query.
+        /// List indexed words of the bucket. Request sets optional limit and
+        /// offset, see [`ListRequest`].
         ///
         /// Note: This method requires enabling the `search` feature and start
-        /// connection in Search mode.
+        /// connection in Search mode. Sonic supports `LIST` since v1.4.0.
         ///
         /// ```rust,no_run
         /// # use sonic_channel::*;
+        /// # #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
         /// # fn main() -> result::Result<()> {
+        /// # futures_lite::future::block_on(async {
         /// let search_channel = SearchChannel::start(
         ///     "localhost:1491",
         ///     "SecretPassword",
-        /// )?;
+        /// ).await?;
         ///
-        /// let result = search_channel.query_with_limit_and_offset(
-        ///     "search",
-        ///     "default",
-        ///     "Beef",
-        ///     10,
-        ///     10,
-        /// )?;
-        /// dbg!(result);
+        /// let dest = Collection::new("search")?.bucket("default")?;
+        /// let words = search_channel.list(ListRequest::new(dest).limit(100)).await?;
+        /// dbg!(words);
         /// # Ok(())
+        /// # })
         /// # }
+        /// # #[cfg(not(any(feature = "runtime-tokio", feature = "rt-async-io")))]
+        /// # fn main() {}
         /// ```
-        use QueryCommand for fn query_with_limit_and_offset<'a>(
-            collection: &'a str,
-            bucket: &'a str,
-            terms: &'a str,
-            limit: usize => Some(limit),
-            offset: usize => Some(offset),
-        )
+        use ListCommand for fn list<'a>(
+            req: ListRequest<'a>,
+        );
+    );
+
+    /// List all indexed words of the bucket page by page with `LIST`
+    /// commands of the given page size.
+    ///
+    /// Note: This method requires enabling the `search` feature and start
+    /// connection in Search mode. Sonic supports `LIST` since v1.4.0.
+    pub async fn list_all(&self, dest: Dest<'_>, page_size: usize) -> Result<Vec<String>> {
+        let mut words = Vec::new();
+        let mut next = Some(ListRequest::new(dest).limit(page_size));
+        while let Some(req) = next {
+            let page = self.list(req).await?;
+            next = req.next_page(page.len());
+            words.extend(page);
+        }
+        Ok(words)
+    }
+}
+
+impl<'a> Pipeline<'a, SearchChannel> {
+    init_command!(
+        /// Query objects in database.
+        pipeline use QueryCommand for fn query<'a>(
+            req: QueryRequest<'a>,
+        );
     );
 
     init_command!(
         /// Suggest auto-completes words.
-        ///
-        /// Note: This method requires enabling the `search` feature and start
-        /// connection in Search mode.
-        ///
-        /// ```rust,no_run
-        /// # use sonic_channel::*;
-        /// # fn main() -> result::Result<()> {
-        /// let search_channel = SearchChannel::start(
-        ///     "localhost:1491",
-        ///     "SecretPassword",
-        /// )?;
-        ///
-        /// let result = search_channel.suggest("search", "default", "Beef")?;
-        /// dbg!(result);
-        /// # Ok(())
-        /// # }
-        /// ```
-        use SuggestCommand for fn suggest<'a>(
-            collection: &'a str,
-            bucket: &'a str,
-            word: &'a str,
+        pipeline use SuggestCommand for fn suggest<'a>(
+            req: SuggestRequest<'a>,
         );
     );
 
     init_command!(
-        /// Suggest auto-completes words with limit.
-        ///
-        /// Note: This method requires enabling the `search` feature and start
-        /// connection in Search mode.
-        ///
-        /// ```rust,no_run
-        /// # use sonic_channel::*;
-        /// # fn main() -> result::Result<()> {
-        /// let search_channel = SearchChannel::start(
-        ///     "localhost:1491",
-        ///     "SecretPassword",
-        /// )?;
-        ///
-        /// let result = search_channel.suggest_with_limit("search", "default", "Beef", 5)?;
-        /// dbg!(result);
-        /// # Ok(())
-        /// # }
-        /// ```
-        use SuggestCommand for fn suggest_with_limit<'a>(
-            collection: &'a str,
-            bucket: &'a str,
-            word: &'a str,
-            limit: usize => Some(limit),
+        /// List indexed words of the bucket.
+        pipeline use ListCommand for fn list<'a>(
+            req: ListRequest<'a>,
         );
     );
 }
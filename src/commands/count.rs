@@ -1,39 +1,54 @@
-use super::StreamCommand;
+use super::{unexpected, StreamCommand, Target};
+use crate::protocol::Request;
 use crate::result::*;
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct CountCommand<'a> {
-    pub collection: &'a str,
-    pub bucket: Option<&'a str>,
-    pub object: Option<&'a str>,
+    pub target: Target<'a>,
 }
 
 impl StreamCommand for CountCommand<'_> {
     type Response = usize;
 
-    fn message(&self) -> String {
-        let mut message = format!("COUNT {}", self.collection);
-        if let Some(bucket) = self.bucket {
-            message.push_str(&format!(" {}", bucket));
+    const IDEMPOTENT: bool = true;
 
-            if let Some(object) = self.object {
-                message.push_str(&format!(" {}", object));
-            }
-        }
-        message.push_str("\r\n");
-        message
+    fn name(&self) -> Option<&'static str> {
+        Some("COUNT")
+    }
+
+    fn message(&self) -> Result<String> {
+        let request = match self.target {
+            Target::Collection(collection) => Request::Count {
+                collection: collection.as_str(),
+                bucket: None,
+                object: None,
+            },
+            Target::Bucket(dest) => Request::Count {
+                collection: dest.collection().as_str(),
+                bucket: Some(dest.bucket().as_str()),
+                object: None,
+            },
+            Target::Object(dest) => Request::Count {
+                collection: dest.collection().as_str(),
+                bucket: Some(dest.bucket().as_str()),
+                object: Some(dest.object().as_str()),
+            },
+        };
+        request.encode()
     }
 
     fn receive(&self, message: String) -> Result<Self::Response> {
         if message.starts_with("RESULT ") {
             let count = message.split_whitespace().last().unwrap_or_default();
-            count.parse().map_err(|_| {
+            count.parse().map_err(|err| {
                 Error::new(ErrorKind::QueryResponseError(
                     "Cannot parse count of count method response to usize",
                 ))
+                .with_source(err)
+                .with_line(&message)
             })
         } else {
-            Err(Error::new(ErrorKind::WrongSonicResponse))
+            Err(unexpected(&message))
         }
     }
 }
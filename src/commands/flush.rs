@@ -1,39 +1,53 @@
-use super::StreamCommand;
+use super::{unexpected, StreamCommand, Target};
+use crate::protocol::Request;
 use crate::result::{Error, ErrorKind, Result};
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct FlushCommand<'a> {
-    pub collection: &'a str,
-    pub bucket: Option<&'a str>,
-    pub object: Option<&'a str>,
+    pub target: Target<'a>,
 }
 
 impl StreamCommand for FlushCommand<'_> {
     type Response = usize;
 
-    fn message(&self) -> String {
-        let mut message = match (self.bucket, self.object) {
-            (Some(bucket), Some(object)) => {
-                format!("FLUSHO {} {} {}", self.collection, bucket, object)
-            }
-            (Some(bucket), None) => format!("FLUSHB {} {}", self.collection, bucket),
-            (None, None) => format!("FLUSHC {}", self.collection),
-            _ => panic!("Invalid flush command"),
+    fn name(&self) -> Option<&'static str> {
+        match self.target {
+            Target::Collection(_) => Some("FLUSHC"),
+            Target::Bucket(_) => Some("FLUSHB"),
+            Target::Object(_) => Some("FLUSHO"),
+        }
+    }
+
+    fn message(&self) -> Result<String> {
+        let request = match self.target {
+            Target::Collection(collection) => Request::FlushCollection {
+                collection: collection.as_str(),
+            },
+            Target::Bucket(dest) => Request::FlushBucket {
+                collection: dest.collection().as_str(),
+                bucket: dest.bucket().as_str(),
+            },
+            Target::Object(dest) => Request::FlushObject {
+                collection: dest.collection().as_str(),
+                bucket: dest.bucket().as_str(),
+                object: dest.object().as_str(),
+            },
         };
-        message.push_str("\r\n");
-        message
+        request.encode()
     }
 
     fn receive(&self, message: String) -> Result<Self::Response> {
         if message.starts_with("RESULT ") {
             let count = message.split_whitespace().last().unwrap_or_default();
-            count.parse().map_err(|_| {
+            count.parse().map_err(|err| {
                 Error::new(ErrorKind::QueryResponseError(
                     "Cannot parse count of flush method response to usize",
                 ))
+                .with_source(err)
+                .with_line(&message)
             })
         } else {
-            Err(Error::new(ErrorKind::WrongSonicResponse))
+            Err(unexpected(&message))
         }
     }
 }
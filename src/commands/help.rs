@@ -0,0 +1,34 @@
+use super::{unexpected, StreamCommand};
+use crate::protocol::{Request, Response};
+use crate::result::*;
+
+/// Asks the server which commands are available in the current mode.
+#[derive(Debug, Default)]
+pub struct HelpCommand;
+
+impl StreamCommand for HelpCommand {
+    type Response = Vec<String>;
+
+    const IDEMPOTENT: bool = true;
+
+    fn message(&self) -> Result<String> {
+        Request::Help(Some("commands")).encode()
+    }
+
+    fn receive(&self, message: String) -> Result<Self::Response> {
+        match Response::parse(&message)? {
+            Response::Fields(fields) => {
+                let commands = fields
+                    .get("commands")
+                    .ok_or_else(|| Error::new(ErrorKind::WrongSonicResponse))?;
+                Ok(commands
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|command| !command.is_empty())
+                    .map(str::to_owned)
+                    .collect())
+            }
+            _ => Err(unexpected(&message)),
+        }
+    }
+}
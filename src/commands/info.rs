@@ -0,0 +1,87 @@
+use super::{unexpected, StreamCommand};
+use crate::protocol::{Request, Response, ResultFields};
+use crate::result::*;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// Statistics of the sonic server returned by `INFO`.
+///
+/// Keys that this version of the crate doesn't know are ignored, keys that
+/// the server didn't send keep zero values.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ServerStats {
+    /// Time since the server was started.
+    pub uptime: Duration,
+    /// Count of connected clients.
+    pub clients_connected: usize,
+    /// Count of commands that the server ran since start.
+    pub commands_total: u64,
+    /// Best latency of a command.
+    pub command_latency_best: Duration,
+    /// Worst latency of a command.
+    pub command_latency_worst: Duration,
+    /// Count of opened KV stores.
+    pub kv_open_count: usize,
+    /// Count of opened FST stores.
+    pub fst_open_count: usize,
+    /// Count of FST stores that wait for consolidation.
+    pub fst_consolidate_count: usize,
+}
+
+#[derive(Debug, Default)]
+pub struct InfoCommand;
+
+impl StreamCommand for InfoCommand {
+    type Response = ServerStats;
+
+    const IDEMPOTENT: bool = true;
+
+    fn name(&self) -> Option<&'static str> {
+        Some("INFO")
+    }
+
+    fn message(&self) -> Result<String> {
+        Request::Info.encode()
+    }
+
+    fn receive(&self, message: String) -> Result<Self::Response> {
+        let fields = match Response::parse(&message)? {
+            Response::Fields(fields) => fields,
+            _ => return Err(unexpected(&message)),
+        };
+        parse_stats(fields).map_err(|err| err.with_line(&message))
+    }
+}
+
+fn parse_stats(fields: ResultFields<'_>) -> Result<ServerStats> {
+    let mut stats = ServerStats::default();
+    for (key, value) in fields.iter() {
+        match key {
+            "uptime" => stats.uptime = Duration::from_secs(parse(value)?),
+            "clients_connected" => stats.clients_connected = parse(value)?,
+            "commands_total" => stats.commands_total = parse(value)?,
+            "command_latency_best" => {
+                stats.command_latency_best = Duration::from_millis(parse(value)?)
+            }
+            "command_latency_worst" => {
+                stats.command_latency_worst = Duration::from_millis(parse(value)?)
+            }
+            "kv_open_count" => stats.kv_open_count = parse(value)?,
+            "fst_open_count" => stats.fst_open_count = parse(value)?,
+            "fst_consolidate_count" => stats.fst_consolidate_count = parse(value)?,
+            _ => {}
+        }
+    }
+    Ok(stats)
+}
+
+fn parse<T>(value: &str) -> Result<T>
+where
+    T: FromStr,
+    T::Err: std::error::Error + Send + Sync + 'static,
+{
+    value
+        .parse()
+        .map_err(|err| Error::new(ErrorKind::WrongSonicResponse).with_source(err))
+}
@@ -0,0 +1,92 @@
+use super::{receive_event, Dest, StreamCommand};
+use crate::protocol::{EventKind, Request};
+use crate::result::*;
+
+/// Parameters of the `LIST` command.
+///
+/// ```rust
+/// # use sonic_channel::*;
+/// # fn main() -> result::Result<()> {
+/// let dest = Collection::new("search")?.bucket("default")?;
+/// let req = ListRequest::new(dest).limit(100);
+///
+/// // The first page is full, so there may be more words.
+/// let next = req.next_page(100).unwrap();
+/// assert_eq!(next, ListRequest::new(dest).limit(100).offset(100));
+/// assert_eq!(next.next_page(42), None);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ListRequest<'a> {
+    dest: Dest<'a>,
+    limit: Option<usize>,
+    offset: Option<usize>,
+}
+
+impl<'a> ListRequest<'a> {
+    /// Creates request to list indexed words of the bucket.
+    pub fn new(dest: Dest<'a>) -> Self {
+        ListRequest {
+            dest,
+            limit: None,
+            offset: None,
+        }
+    }
+
+    /// Sets maximum count of words in the response.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Sets count of words to skip.
+    pub fn offset(mut self, offset: usize) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// Returns request of the next page if the response of this request had
+    /// `received` words and filled the whole page. Requests without limit
+    /// have no pages.
+    pub fn next_page(&self, received: usize) -> Option<Self> {
+        match self.limit {
+            Some(limit) if limit > 0 && received >= limit => {
+                Some(self.offset(self.offset.unwrap_or(0) + received))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ListCommand<'a> {
+    pub req: ListRequest<'a>,
+}
+
+impl StreamCommand for ListCommand<'_> {
+    type Response = Vec<String>;
+
+    const READ_LINES_COUNT: usize = 2;
+
+    const IDEMPOTENT: bool = true;
+
+    fn name(&self) -> Option<&'static str> {
+        Some("LIST")
+    }
+
+    fn message(&self) -> Result<String> {
+        Request::List {
+            collection: self.req.dest.collection().as_str(),
+            bucket: self.req.dest.bucket().as_str(),
+            limit: self.req.limit,
+            offset: self.req.offset,
+        }
+        .encode()
+    }
+
+    fn receive(&self, message: String) -> Result<Self::Response> {
+        let objects = receive_event(&message, EventKind::List)?;
+        Ok(objects.iter().map(str::to_owned).collect())
+    }
+}
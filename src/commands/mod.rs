@@ -1,6 +1,7 @@
 mod quit;
 mod start;
 
+mod help;
 mod ping;
 
 #[cfg(feature = "ingest")]
@@ -12,17 +13,22 @@ mod pop;
 #[cfg(feature = "ingest")]
 mod push;
 
+#[cfg(feature = "search")]
+mod list;
 #[cfg(feature = "search")]
 mod query;
 #[cfg(feature = "search")]
 mod suggest;
 
+#[cfg(feature = "control")]
+mod info;
 #[cfg(feature = "control")]
 mod trigger;
 
 pub(crate) use quit::QuitCommand;
 pub(crate) use start::StartCommand;
 
+pub(crate) use help::HelpCommand;
 pub(crate) use ping::PingCommand;
 
 #[cfg(feature = "ingest")]
@@ -34,22 +40,152 @@ pub(crate) use pop::PopCommand;
 #[cfg(feature = "ingest")]
 pub(crate) use push::PushCommand;
 
+#[cfg(feature = "search")]
+pub(crate) use list::ListCommand;
+#[cfg(feature = "search")]
+pub use list::ListRequest;
 #[cfg(feature = "search")]
 pub(crate) use query::QueryCommand;
 #[cfg(feature = "search")]
+pub use query::QueryRequest;
+#[cfg(feature = "search")]
 pub(crate) use suggest::SuggestCommand;
+#[cfg(feature = "search")]
+pub use suggest::SuggestRequest;
 
 #[cfg(feature = "control")]
-pub(crate) use trigger::{TriggerAction, TriggerCommand};
+pub(crate) use crate::protocol::TriggerAction;
+#[cfg(feature = "control")]
+pub(crate) use info::InfoCommand;
+#[cfg(feature = "control")]
+pub use info::ServerStats;
+#[cfg(feature = "control")]
+pub(crate) use trigger::TriggerCommand;
 
+#[cfg(any(feature = "ingest", feature = "search"))]
+pub(crate) use crate::ident::Dest;
+#[cfg(feature = "ingest")]
+pub(crate) use crate::ident::{Collection, ObjDest};
+
+use crate::protocol::Response;
+#[cfg(feature = "search")]
+use crate::protocol::{EventKind, Objects};
 use crate::result::Result;
+use crate::result::{Error, ErrorKind};
 
 pub trait StreamCommand {
     type Response;
 
     const READ_LINES_COUNT: usize = 1;
 
-    fn message(&self) -> String;
+    /// Command doesn't change anything on the server, so it can be sent
+    /// again if connection was lost before the response came.
+    const IDEMPOTENT: bool = false;
+
+    fn message(&self) -> Result<String>;
 
     fn receive(&self, message: String) -> Result<Self::Response>;
+
+    /// Name of the command that is checked by the server info before the
+    /// command is sent. Commands that every server supports have no name.
+    fn name(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Splits command into several ones whose messages fit into the server
+    /// buffer. Parts are run in order and the response of the last one is
+    /// returned. Most commands can't be split and are sent as is.
+    fn split(self, _max_buffer_size: usize) -> Result<Vec<Self>>
+    where
+        Self: Sized,
+    {
+        Ok(vec![self])
+    }
+}
+
+/// Checks that the encoded command fits into the server buffer.
+pub(crate) fn fit_buffer(message: String, max_buffer_size: usize) -> Result<String> {
+    if message.len() > max_buffer_size {
+        Err(Error::new(ErrorKind::CommandTooLong {
+            length: message.len(),
+            max_buffer_size,
+        }))
+    } else {
+        Ok(message)
+    }
+}
+
+/// Returns error for the response that doesn't answer the command. `ERR`
+/// and `ENDED` lines tell why the server didn't run it, any other line means
+/// that the client and the server don't understand each other.
+pub(crate) fn unexpected(message: &str) -> Error {
+    for line in message.lines() {
+        match Response::parse(line) {
+            Ok(Response::Err(reason)) => return Error::server(reason).with_line(line),
+            Ok(Response::Ended(reason)) => {
+                return Error::new(ErrorKind::ConnectionEnded(reason.to_owned())).with_line(line)
+            }
+            _ => {}
+        }
+    }
+    Error::new(ErrorKind::WrongSonicResponse).with_line(message)
+}
+
+/// Parses `PENDING` line and the following `EVENT` line of the given kind
+/// and returns objects of the event.
+#[cfg(feature = "search")]
+pub(crate) fn receive_event(message: &str, kind: EventKind) -> Result<Objects<'_>> {
+    let (pending, event) = message
+        .split_once('\n')
+        .ok_or_else(|| unexpected(message))?;
+    match (Response::parse(pending), Response::parse(event)) {
+        (
+            Ok(Response::Pending(pending_id)),
+            Ok(Response::Event {
+                kind: event_kind,
+                id,
+                objects,
+            }),
+        ) if event_kind == kind => {
+            if pending_id == id {
+                Ok(objects)
+            } else {
+                Err(Error::new(ErrorKind::QueryResponseError(
+                    "Pending id and event id don't match",
+                ))
+                .with_line(message))
+            }
+        }
+        _ => Err(unexpected(message)),
+    }
+}
+
+/// Collection, bucket or object that flush and count commands work with.
+#[cfg(feature = "ingest")]
+#[derive(Debug, Clone, Copy)]
+pub enum Target<'a> {
+    Collection(Collection<'a>),
+    Bucket(Dest<'a>),
+    Object(ObjDest<'a>),
+}
+
+#[cfg(feature = "ingest")]
+impl<'a> From<Collection<'a>> for Target<'a> {
+    fn from(collection: Collection<'a>) -> Self {
+        Target::Collection(collection)
+    }
+}
+
+#[cfg(feature = "ingest")]
+impl<'a> From<Dest<'a>> for Target<'a> {
+    fn from(dest: Dest<'a>) -> Self {
+        Target::Bucket(dest)
+    }
+}
+
+#[cfg(feature = "ingest")]
+impl<'a> From<ObjDest<'a>> for Target<'a> {
+    fn from(dest: ObjDest<'a>) -> Self {
+        Target::Object(dest)
+    }
 }
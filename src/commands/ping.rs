@@ -1,4 +1,5 @@
-use super::StreamCommand;
+use super::{unexpected, StreamCommand};
+use crate::protocol::Request;
 use crate::result::*;
 
 #[derive(Debug, Default)]
@@ -7,16 +8,17 @@ pub struct PingCommand;
 impl StreamCommand for PingCommand {
     type Response = bool;
 
-    fn message(&self) -> String {
-        String::from("PING\r\n")
+    const IDEMPOTENT: bool = true;
+
+    fn message(&self) -> Result<String> {
+        Request::Ping.encode()
     }
 
     fn receive(&self, message: String) -> Result<Self::Response> {
-        dbg!(&message);
         if message == "PONG\r\n" {
             Ok(true)
         } else {
-            Err(Error::new(ErrorKind::WrongSonicResponse))
+            Err(unexpected(&message))
         }
     }
 }
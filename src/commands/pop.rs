@@ -1,36 +1,42 @@
-use super::StreamCommand;
+use super::{unexpected, ObjDest, StreamCommand};
+use crate::protocol::Request;
 use crate::result::*;
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct PopCommand<'a> {
-    pub collection: &'a str,
-    pub bucket: &'a str,
-    pub object: &'a str,
+    pub dest: ObjDest<'a>,
     pub text: &'a str,
 }
 
 impl StreamCommand for PopCommand<'_> {
     type Response = usize;
 
-    fn message(&self) -> String {
-        let mut message = format!(
-            r#"POP {} {} {} "{}""#,
-            self.collection, self.bucket, self.object, self.text
-        );
-        message.push_str("\r\n");
-        message
+    fn name(&self) -> Option<&'static str> {
+        Some("POP")
+    }
+
+    fn message(&self) -> Result<String> {
+        Request::Pop {
+            collection: self.dest.collection().as_str(),
+            bucket: self.dest.bucket().as_str(),
+            object: self.dest.object().as_str(),
+            text: self.text,
+        }
+        .encode()
     }
 
     fn receive(&self, message: String) -> Result<Self::Response> {
         if message.starts_with("RESULT ") {
             let count = message.split_whitespace().last().unwrap_or_default();
-            count.parse().map_err(|_| {
+            count.parse().map_err(|err| {
                 Error::new(ErrorKind::QueryResponseError(
                     "Cannot parse count of pop method response to usize",
                 ))
+                .with_source(err)
+                .with_line(&message)
             })
         } else {
-            Err(Error::new(ErrorKind::WrongSonicResponse))
+            Err(unexpected(&message))
         }
     }
 }
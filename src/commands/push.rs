@@ -1,37 +1,60 @@
-use super::StreamCommand;
+use super::{unexpected, ObjDest, StreamCommand};
+use crate::lang::Lang;
+use crate::protocol::{quoted_len, split_text, Request};
 use crate::result::*;
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct PushCommand<'a> {
-    pub collection: &'a str,
-    pub bucket: &'a str,
-    pub object: &'a str,
+    pub dest: ObjDest<'a>,
     pub text: &'a str,
-    pub locale: Option<&'a str>,
+    pub locale: Option<Lang>,
 }
 
 impl StreamCommand for PushCommand<'_> {
     type Response = bool;
 
-    fn message(&self) -> String {
-        let mut message = format!(
-            r#"PUSH {} {} {} "{}""#,
-            self.collection, self.bucket, self.object, self.text
-        );
-        if let Some(locale) = self.locale.as_ref() {
-            message.push_str(&format!(" LANG({})", locale));
+    fn name(&self) -> Option<&'static str> {
+        Some("PUSH")
+    }
+
+    fn message(&self) -> Result<String> {
+        Request::Push {
+            collection: self.dest.collection().as_str(),
+            bucket: self.dest.bucket().as_str(),
+            object: self.dest.object().as_str(),
+            text: self.text,
+            locale: self.locale.map(|lang| lang.code()),
         }
-        message.push_str("\r\n");
-        message
+        .encode()
     }
 
     fn receive(&self, message: String) -> Result<Self::Response> {
-        dbg!(&message);
-
         if message == "OK\r\n" {
             Ok(true)
         } else {
-            Err(Error::new(ErrorKind::WrongSonicResponse))
+            Err(unexpected(&message))
         }
     }
+
+    /// Splits long text at word boundaries, each part is pushed to the same
+    /// object.
+    fn split(self, max_buffer_size: usize) -> Result<Vec<Self>> {
+        let message = self.message()?;
+        if message.len() <= max_buffer_size {
+            return Ok(vec![self]);
+        }
+
+        let max_text_len = (max_buffer_size + quoted_len(self.text))
+            .saturating_sub(message.len() + quoted_len(""));
+        let parts = split_text(self.text, max_text_len).ok_or_else(|| {
+            Error::new(ErrorKind::CommandTooLong {
+                length: message.len(),
+                max_buffer_size,
+            })
+        })?;
+        Ok(parts
+            .into_iter()
+            .map(|text| PushCommand { text, ..self })
+            .collect())
+    }
 }
@@ -1,19 +1,65 @@
-use super::StreamCommand;
+use super::{receive_event, Dest, StreamCommand};
+use crate::lang::Lang;
+use crate::protocol::{EventKind, Request};
 use crate::result::*;
-use regex::Regex;
 
-const RE_QUERY_RECEIVED_MESSAGE: &str = r"(?x)
-    ^PENDING\s(?P<pending_query_id>\w+)\r\n
-    EVENT\sQUERY\s(?P<event_query_id>\w+)\s(?P<objects>.*?)\r\n$
-";
+/// Parameters of the `QUERY` command.
+///
+/// ```rust
+/// # use sonic_channel::*;
+/// # fn main() -> result::Result<()> {
+/// let dest = Collection::new("search")?.bucket("default")?;
+/// let req = QueryRequest::new(dest, "Beef")
+///     .limit(10)
+///     .offset(20)
+///     .lang(Lang::Eng);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct QueryRequest<'a> {
+    dest: Dest<'a>,
+    terms: &'a str,
+    limit: Option<usize>,
+    offset: Option<usize>,
+    lang: Option<Lang>,
+}
+
+impl<'a> QueryRequest<'a> {
+    /// Creates request to search the terms in the bucket.
+    pub fn new(dest: Dest<'a>, terms: &'a str) -> Self {
+        QueryRequest {
+            dest,
+            terms,
+            limit: None,
+            offset: None,
+            lang: None,
+        }
+    }
+
+    /// Sets maximum count of object ids in the response.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
 
-#[derive(Debug, Default)]
+    /// Sets count of object ids to skip.
+    pub fn offset(mut self, offset: usize) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// Sets language of the terms instead of detection by the server.
+    /// [`Lang::None`] disables the lexer for the terms.
+    pub fn lang(mut self, lang: Lang) -> Self {
+        self.lang = Some(lang);
+        self
+    }
+}
+
+#[derive(Debug)]
 pub struct QueryCommand<'a> {
-    pub collection: &'a str,
-    pub bucket: &'a str,
-    pub terms: &'a str,
-    pub limit: Option<usize>,
-    pub offset: Option<usize>,
+    pub req: QueryRequest<'a>,
 }
 
 impl StreamCommand for QueryCommand<'_> {
@@ -21,43 +67,54 @@ impl StreamCommand for QueryCommand<'_> {
 
     const READ_LINES_COUNT: usize = 2;
 
-    fn message(&self) -> String {
-        let mut message = format!(
-            r#"QUERY {} {} "{}""#,
-            self.collection, self.bucket, self.terms
-        );
-        if let Some(limit) = self.limit.as_ref() {
-            message.push_str(&format!(" LIMIT({})", limit));
-        }
-        if let Some(offset) = self.offset.as_ref() {
-            message.push_str(&format!(" OFFSET({})", offset));
+    const IDEMPOTENT: bool = true;
+
+    fn name(&self) -> Option<&'static str> {
+        Some("QUERY")
+    }
+
+    fn message(&self) -> Result<String> {
+        Request::Query {
+            collection: self.req.dest.collection().as_str(),
+            bucket: self.req.dest.bucket().as_str(),
+            terms: self.req.terms,
+            limit: self.req.limit,
+            offset: self.req.offset,
+            lang: self.req.lang.map(|lang| lang.code()),
         }
-        message.push_str("\r\n");
-        message
+        .encode()
     }
 
     fn receive(&self, message: String) -> Result<Self::Response> {
-        lazy_static! {
-            static ref RE: Regex = Regex::new(RE_QUERY_RECEIVED_MESSAGE).unwrap();
-        }
+        let objects = receive_event(&message, EventKind::Query)?;
+        Ok(objects.iter().map(str::to_owned).collect())
+    }
+}
 
-        dbg!(&message);
-
-        if let Some(caps) = RE.captures(&message) {
-            if caps["pending_query_id"] != caps["event_query_id"] {
-                Err(Error::new(ErrorKind::QueryResponseError(
-                    "Pending id and event id don't match",
-                )))
-            } else if caps["objects"].is_empty() {
-                Ok(vec![])
-            } else {
-                Ok(caps["objects"]
-                    .split_whitespace()
-                    .map(str::to_owned)
-                    .collect())
-            }
-        } else {
-            Err(Error::new(ErrorKind::WrongSonicResponse))
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ident::Collection;
+
+    #[test]
+    fn encode_lang_option() {
+        let dest = Collection::new("messages")
+            .unwrap()
+            .bucket("default")
+            .unwrap();
+        let message = |lang| {
+            let req = QueryRequest::new(dest, "hello").lang(lang);
+            QueryCommand { req }.message().unwrap()
+        };
+
+        assert_eq!(
+            message(Lang::Eng),
+            "QUERY messages default \"hello\" LANG(eng)\r\n"
+        );
+        // Sonic disables the lexer for the terms.
+        assert_eq!(
+            message(Lang::None),
+            "QUERY messages default \"hello\" LANG(none)\r\n"
+        );
     }
 }
@@ -1,4 +1,5 @@
-use super::StreamCommand;
+use super::{unexpected, StreamCommand};
+use crate::protocol::Request;
 use crate::result::*;
 
 #[derive(Debug, Default)]
@@ -7,16 +8,15 @@ pub struct QuitCommand;
 impl StreamCommand for QuitCommand {
     type Response = bool;
 
-    fn message(&self) -> String {
-        String::from("QUIT\r\n")
+    fn message(&self) -> Result<String> {
+        Request::Quit.encode()
     }
 
     fn receive(&self, message: String) -> Result<Self::Response> {
-        dbg!(&message);
         if message.starts_with("ENDED ") {
             Ok(true)
         } else {
-            Err(Error::new(ErrorKind::WrongSonicResponse))
+            Err(unexpected(&message))
         }
     }
 }
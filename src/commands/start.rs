@@ -1,17 +1,7 @@
-use super::StreamCommand;
+use super::{unexpected, StreamCommand};
 use crate::channels::ChannelMode;
+use crate::protocol::{Request, Response};
 use crate::result::*;
-use regex::Regex;
-
-const RE_START_RECEIVED_MESSAGE: &str = r"(?x)
-    STARTED 
-    \s # started with mode
-    (?P<mode>search|ingest|control) 
-    \s # wich protocol used
-    protocol\((?P<protocol>\d+)\) 
-    \s # maximum buffer size
-    buffer\((?P<buffer_size>\d+)\)
-";
 
 #[derive(Debug)]
 pub struct StartCommand {
@@ -29,34 +19,27 @@ pub struct StartCommandResponse {
 impl StreamCommand for StartCommand {
     type Response = StartCommandResponse;
 
-    fn message(&self) -> String {
-        format!("START {} {}\r\n", self.mode, self.password)
+    fn message(&self) -> Result<String> {
+        Request::Start {
+            mode: self.mode,
+            password: &self.password,
+        }
+        .encode()
     }
 
     fn receive(&self, message: String) -> Result<Self::Response> {
-        lazy_static! {
-            static ref RE: Regex = Regex::new(RE_START_RECEIVED_MESSAGE).unwrap();
-        }
-
-        dbg!(&message);
-
-        if let Some(caps) = RE.captures(&message) {
-            if self.mode.to_str() != &caps["mode"] {
-                Err(Error::new(ErrorKind::SwitchMode))
-            } else {
-                let protocol_version: usize =
-                    caps["protocol"].parse().expect("Must be digit by regex");
-                let max_buffer_size: usize =
-                    caps["buffer_size"].parse().expect("Must be digit by regex");
-
-                Ok(StartCommandResponse {
-                    protocol_version,
-                    max_buffer_size,
-                    mode: self.mode,
-                })
-            }
-        } else {
-            Err(Error::new(ErrorKind::SwitchMode))
+        match Response::parse(&message) {
+            Ok(Response::Started {
+                mode,
+                protocol_version,
+                max_buffer_size,
+            }) if mode == self.mode.to_str() => Ok(StartCommandResponse {
+                protocol_version,
+                max_buffer_size,
+                mode: self.mode,
+            }),
+            Ok(Response::Err(_)) | Ok(Response::Ended(_)) => Err(unexpected(&message)),
+            _ => Err(Error::new(ErrorKind::SwitchMode)),
         }
     }
 }
@@ -1,18 +1,44 @@
-use super::StreamCommand;
+use super::{receive_event, Dest, StreamCommand};
+use crate::protocol::{EventKind, Request};
 use crate::result::*;
-use regex::Regex;
 
-const RE_SUGGEST_RECEIVED_MESSAGE: &str = r"(?x)
-    ^PENDING\s(?P<pending_suggest_id>\w+)\r\n
-    EVENT\sSUGGEST\s(?P<event_suggest_id>\w+)\s(?P<words>.*?)\r\n$
-";
+/// Parameters of the `SUGGEST` command.
+///
+/// ```rust
+/// # use sonic_channel::*;
+/// # fn main() -> result::Result<()> {
+/// let dest = Collection::new("search")?.bucket("default")?;
+/// let req = SuggestRequest::new(dest, "bee").limit(5);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SuggestRequest<'a> {
+    dest: Dest<'a>,
+    word: &'a str,
+    limit: Option<usize>,
+}
+
+impl<'a> SuggestRequest<'a> {
+    /// Creates request to complete the word in the bucket.
+    pub fn new(dest: Dest<'a>, word: &'a str) -> Self {
+        SuggestRequest {
+            dest,
+            word,
+            limit: None,
+        }
+    }
+
+    /// Sets maximum count of words in the response.
+    pub fn limit(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+}
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct SuggestCommand<'a> {
-    pub collection: &'a str,
-    pub bucket: &'a str,
-    pub word: &'a str,
-    pub limit: Option<usize>,
+    pub req: SuggestRequest<'a>,
 }
 
 impl StreamCommand for SuggestCommand<'_> {
@@ -20,41 +46,24 @@ impl StreamCommand for SuggestCommand<'_> {
 
     const READ_LINES_COUNT: usize = 2;
 
-    fn message(&self) -> String {
-        let mut message = format!(
-            r#"SUGGEST {} {} "{}""#,
-            self.collection, self.bucket, self.word
-        );
-        if let Some(limit) = self.limit.as_ref() {
-            message.push_str(&format!(" LIMIT({})", limit));
-        }
-        message.push_str("\r\n");
-        message
+    const IDEMPOTENT: bool = true;
+
+    fn name(&self) -> Option<&'static str> {
+        Some("SUGGEST")
     }
 
-    fn receive(&self, message: String) -> Result<Self::Response> {
-        lazy_static! {
-            static ref RE: Regex = Regex::new(RE_SUGGEST_RECEIVED_MESSAGE).unwrap();
+    fn message(&self) -> Result<String> {
+        Request::Suggest {
+            collection: self.req.dest.collection().as_str(),
+            bucket: self.req.dest.bucket().as_str(),
+            word: self.req.word,
+            limit: self.req.limit,
         }
+        .encode()
+    }
 
-        dbg!(&message);
-
-        match RE.captures(&message) {
-            None => Err(Error::new(ErrorKind::WrongSonicResponse)),
-            Some(caps) => {
-                if caps["pending_suggest_id"] != caps["event_suggest_id"] {
-                    Err(Error::new(ErrorKind::QueryResponseError(
-                        "Pending id and event id don't match",
-                    )))
-                } else if caps["words"].is_empty() {
-                    Ok(vec![])
-                } else {
-                    Ok(caps["words"]
-                        .split_whitespace()
-                        .map(str::to_owned)
-                        .collect())
-                }
-            }
-        }
+    fn receive(&self, message: String) -> Result<Self::Response> {
+        let objects = receive_event(&message, EventKind::Suggest)?;
+        Ok(objects.iter().map(str::to_owned).collect())
     }
 }
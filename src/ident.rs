@@ -0,0 +1,167 @@
+use crate::result::*;
+use std::convert::TryFrom;
+use std::fmt;
+
+/// Checks that the name can be sent as one word of the command.
+pub(crate) fn validate(name: &str, message: &'static str) -> Result<()> {
+    let valid = !name.is_empty()
+        && !name
+            .chars()
+            .any(|c| c.is_whitespace() || c.is_control() || c == '"');
+    if valid {
+        Ok(())
+    } else {
+        Err(Error::new(ErrorKind::InvalidInput(message)))
+    }
+}
+
+macro_rules! ident {
+    (
+        $(#[$outer:meta])*
+        $name:ident, $message:literal
+    ) => {
+        $(#[$outer])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub struct $name<'a>(&'a str);
+
+        impl<'a> $name<'a> {
+            /// Checks the name and wraps it. Name must be non-empty and must
+            /// not contain whitespace, control characters or quotes.
+            pub fn new(name: &'a str) -> Result<Self> {
+                validate(name, $message)?;
+                Ok(Self(name))
+            }
+
+            /// Returns the name.
+            pub fn as_str(&self) -> &'a str {
+                self.0
+            }
+        }
+
+        impl<'a> TryFrom<&'a str> for $name<'a> {
+            type Error = Error;
+
+            fn try_from(name: &'a str) -> Result<Self> {
+                Self::new(name)
+            }
+        }
+
+        impl fmt::Display for $name<'_> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str(self.0)
+            }
+        }
+    };
+}
+
+ident!(
+    /// Name of the sonic collection.
+    Collection,
+    "Collection must be a non-empty word without quotes"
+);
+
+ident!(
+    /// Name of the bucket in a collection.
+    Bucket,
+    "Bucket must be a non-empty word without quotes"
+);
+
+ident!(
+    /// Id of the object in a bucket.
+    ObjectId,
+    "Object id must be a non-empty word without quotes"
+);
+
+impl<'a> Collection<'a> {
+    /// Checks the bucket name and makes destination in this collection.
+    ///
+    /// ```rust
+    /// # use sonic_channel::*;
+    /// # fn main() -> result::Result<()> {
+    /// let dest = Collection::new("search")?.bucket("default")?;
+    /// assert_eq!(dest.bucket().as_str(), "default");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn bucket(self, bucket: &'a str) -> Result<Dest<'a>> {
+        Ok(Dest::new(self, Bucket::new(bucket)?))
+    }
+}
+
+/// Bucket in a collection, used by search commands and commands that work
+/// with the whole bucket.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Dest<'a> {
+    collection: Collection<'a>,
+    bucket: Bucket<'a>,
+}
+
+impl<'a> Dest<'a> {
+    /// Makes destination from checked names.
+    pub fn new(collection: Collection<'a>, bucket: Bucket<'a>) -> Self {
+        Dest { collection, bucket }
+    }
+
+    /// Returns collection of the destination.
+    pub fn collection(&self) -> Collection<'a> {
+        self.collection
+    }
+
+    /// Returns bucket of the destination.
+    pub fn bucket(&self) -> Bucket<'a> {
+        self.bucket
+    }
+
+    /// Checks the object id and makes destination of the object in this
+    /// bucket.
+    ///
+    /// ```rust
+    /// # use sonic_channel::*;
+    /// # fn main() -> result::Result<()> {
+    /// let dest = Collection::new("search")?
+    ///     .bucket("default")?
+    ///     .object("recipe:295")?;
+    /// assert_eq!(dest.object().as_str(), "recipe:295");
+    ///
+    /// assert!(dest.dest().object("recipe 296").is_err());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn object(self, object: &'a str) -> Result<ObjDest<'a>> {
+        Ok(ObjDest::new(self, ObjectId::new(object)?))
+    }
+}
+
+/// Object in a bucket, used by commands that change indexed data.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ObjDest<'a> {
+    dest: Dest<'a>,
+    object: ObjectId<'a>,
+}
+
+impl<'a> ObjDest<'a> {
+    /// Makes destination of the object from checked names.
+    pub fn new(dest: Dest<'a>, object: ObjectId<'a>) -> Self {
+        ObjDest { dest, object }
+    }
+
+    /// Returns destination of the bucket with the object.
+    pub fn dest(&self) -> Dest<'a> {
+        self.dest
+    }
+
+    /// Returns collection of the object.
+    pub fn collection(&self) -> Collection<'a> {
+        self.dest.collection
+    }
+
+    /// Returns bucket of the object.
+    pub fn bucket(&self) -> Bucket<'a> {
+        self.dest.bucket
+    }
+
+    /// Returns object id.
+    pub fn object(&self) -> ObjectId<'a> {
+        self.object
+    }
+}
@@ -0,0 +1,120 @@
+use std::fmt;
+
+macro_rules! langs {
+    ($($variant:ident => $code:literal, $name:literal;)*) => {
+        /// Language of the text in ISO 639-3 code. Sonic detects language of
+        /// the text by itself, set it only if you know the language better.
+        /// [`Lang::None`] turns the lexer off, so the text is used as is.
+        ///
+        /// ```rust
+        /// use sonic_channel::Lang;
+        ///
+        /// assert_eq!(Lang::Rus.code(), "rus");
+        /// assert_eq!(Lang::from_code("eng"), Some(Lang::Eng));
+        /// assert_eq!(Lang::None.code(), "none");
+        /// ```
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum Lang {
+            $(
+                #[doc = $name]
+                $variant,
+            )*
+        }
+
+        impl Lang {
+            /// Returns ISO 639-3 code of the language or `none`.
+            pub fn code(&self) -> &'static str {
+                match self {
+                    $(Lang::$variant => $code,)*
+                }
+            }
+
+            /// Returns language by ISO 639-3 code or `none` if sonic supports it.
+            pub fn from_code(code: &str) -> Option<Self> {
+                match code {
+                    $($code => Some(Lang::$variant),)*
+                    _ => None,
+                }
+            }
+        }
+    };
+}
+
+langs! {
+    None => "none", "No language, the lexer is disabled";
+    Afr => "afr", "Afrikaans";
+    Aka => "aka", "Akan";
+    Amh => "amh", "Amharic";
+    Ara => "ara", "Arabic";
+    Aze => "aze", "Azerbaijani";
+    Bel => "bel", "Belarusian";
+    Ben => "ben", "Bengali";
+    Bul => "bul", "Bulgarian";
+    Cat => "cat", "Catalan";
+    Ces => "ces", "Czech";
+    Cmn => "cmn", "Mandarin";
+    Dan => "dan", "Danish";
+    Deu => "deu", "German";
+    Ell => "ell", "Greek";
+    Eng => "eng", "English";
+    Epo => "epo", "Esperanto";
+    Est => "est", "Estonian";
+    Fin => "fin", "Finnish";
+    Fra => "fra", "French";
+    Guj => "guj", "Gujarati";
+    Heb => "heb", "Hebrew";
+    Hin => "hin", "Hindi";
+    Hrv => "hrv", "Croatian";
+    Hun => "hun", "Hungarian";
+    Hye => "hye", "Armenian";
+    Ind => "ind", "Indonesian";
+    Ita => "ita", "Italian";
+    Jav => "jav", "Javanese";
+    Jpn => "jpn", "Japanese";
+    Kan => "kan", "Kannada";
+    Kat => "kat", "Georgian";
+    Khm => "khm", "Khmer";
+    Kor => "kor", "Korean";
+    Lat => "lat", "Latin";
+    Lav => "lav", "Latvian";
+    Lit => "lit", "Lithuanian";
+    Mal => "mal", "Malayalam";
+    Mar => "mar", "Marathi";
+    Mkd => "mkd", "Macedonian";
+    Mya => "mya", "Burmese";
+    Nep => "nep", "Nepali";
+    Nld => "nld", "Dutch";
+    Nob => "nob", "Norwegian Bokmål";
+    Ori => "ori", "Oriya";
+    Pan => "pan", "Punjabi";
+    Pes => "pes", "Persian";
+    Pol => "pol", "Polish";
+    Por => "por", "Portuguese";
+    Ron => "ron", "Romanian";
+    Rus => "rus", "Russian";
+    Sin => "sin", "Sinhala";
+    Slk => "slk", "Slovak";
+    Slv => "slv", "Slovenian";
+    Sna => "sna", "Shona";
+    Spa => "spa", "Spanish";
+    Srp => "srp", "Serbian";
+    Swe => "swe", "Swedish";
+    Tam => "tam", "Tamil";
+    Tel => "tel", "Telugu";
+    Tgl => "tgl", "Tagalog";
+    Tha => "tha", "Thai";
+    Tuk => "tuk", "Turkmen";
+    Tur => "tur", "Turkish";
+    Ukr => "ukr", "Ukrainian";
+    Urd => "urd", "Urdu";
+    Uzb => "uzb", "Uzbek";
+    Vie => "vie", "Vietnamese";
+    Yid => "yid", "Yiddish";
+    Zul => "zul", "Zulu";
+}
+
+impl fmt::Display for Lang {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
@@ -4,6 +4,9 @@
 //!
 //! ## Example usage
 //!
+//! All channels in the crate root are asynchronous. If you don't have any
+//! async runtime, look at the [`blocking`] module.
+//!
 //! ### Search channel
 //!
 //! Note: This example requires enabling the `search` feature, enabled by default.
@@ -11,17 +14,23 @@
 //! ```rust,no_run
 //! use sonic_channel::*;
 //!
+//! # #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
 //! fn main() -> result::Result<()> {
-//!     let channel = SearchChannel::start(
-//!         "localhost:1491",
-//!         "SecretPassword",
-//!     )?;
-//!
-//!     let objects = channel.query("collection", "bucket", "recipe")?;
-//!     dbg!(objects);
-//!
-//!     Ok(())
+//!     futures_lite::future::block_on(async {
+//!         let channel = SearchChannel::start(
+//!             "localhost:1491",
+//!             "SecretPassword",
+//!         ).await?;
+//!
+//!         let dest = Collection::new("collection")?.bucket("bucket")?;
+//!         let objects = channel.query(QueryRequest::new(dest, "recipe")).await?;
+//!         dbg!(objects);
+//!
+//!         Ok(())
+//!     })
 //! }
+//! # #[cfg(not(any(feature = "runtime-tokio", feature = "rt-async-io")))]
+//! # fn main() {}
 //! ```
 //!
 //! ### Ingest channel
@@ -31,19 +40,27 @@
 //! ```rust,no_run
 //! use sonic_channel::*;
 //!
+//! # #[cfg(all(feature = "ingest", any(feature = "runtime-tokio", feature = "rt-async-io")))]
 //! fn main() -> result::Result<()> {
-//!     let mut channel = IngestChannel::start(
-//!         "localhost:1491",
-//!         "SecretPassword",
-//!     )?;
-//!
-//!     let pushed = channel.push("collection", "bucket", "object:1", "my best recipe")?;
-//!     // or
-//!     // let pushed = channel.push_with_locale("collection", "bucket", "object:1", "Мой лучший рецепт", "rus")?;
-//!     dbg!(pushed);
-//!
-//!     Ok(())
+//!     futures_lite::future::block_on(async {
+//!         let channel = IngestChannel::start(
+//!             "localhost:1491",
+//!             "SecretPassword",
+//!         ).await?;
+//!
+//!         let dest = Collection::new("collection")?
+//!             .bucket("bucket")?
+//!             .object("object:1")?;
+//!         let pushed = channel.push(dest, "my best recipe").await?;
+//!         // or
+//!         // let pushed = channel.push_with_locale(dest, "Мой лучший рецепт", Lang::Rus).await?;
+//!         dbg!(pushed);
+//!
+//!         Ok(())
+//!     })
 //! }
+//! # #[cfg(not(all(feature = "ingest", any(feature = "runtime-tokio", feature = "rt-async-io"))))]
+//! # fn main() {}
 //! ```
 //!
 //! ### Control channel
@@ -53,17 +70,47 @@
 //! ```rust,no_run
 //! use sonic_channel::*;
 //!
+//! # #[cfg(all(feature = "control", any(feature = "runtime-tokio", feature = "rt-async-io")))]
 //! fn main() -> result::Result<()> {
-//!     let mut channel = ControlChannel::start(
+//!     futures_lite::future::block_on(async {
+//!         let channel = ControlChannel::start(
+//!             "localhost:1491",
+//!             "SecretPassword",
+//!         ).await?;
+//!
+//!         let result = channel.consolidate().await?;
+//!         assert_eq!(result, true);
+//!
+//!         Ok(())
+//!     })
+//! }
+//! # #[cfg(not(all(feature = "control", any(feature = "runtime-tokio", feature = "rt-async-io"))))]
+//! # fn main() {}
+//! ```
+//!
+//! ### Blocking channels
+//!
+//! Note: This example requires enabling the `blocking` feature.
+//!
+//! ```rust,no_run
+//! # #[cfg(feature = "blocking")]
+//! use sonic_channel::blocking::*;
+//!
+//! # #[cfg(feature = "blocking")]
+//! fn main() -> sonic_channel::result::Result<()> {
+//!     let channel = SearchChannel::start(
 //!         "localhost:1491",
 //!         "SecretPassword",
 //!     )?;
 //!
-//!     let result = channel.consolidate()?;
-//!     assert_eq!(result, true);
+//!     let dest = Collection::new("collection")?.bucket("bucket")?;
+//!     let objects = channel.query(QueryRequest::new(dest, "recipe"))?;
+//!     dbg!(objects);
 //!
 //!     Ok(())
 //! }
+//! # #[cfg(not(feature = "blocking"))]
+//! # fn main() {}
 //! ```
 //!
 //! [sonic]: https://github.com/valeriansaliou/sonic
@@ -90,15 +137,52 @@ mod macroses;
 
 mod channels;
 mod commands;
+mod ident;
+mod lang;
+mod options;
+#[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
+mod pool;
+#[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
+mod reconnect;
+#[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
+mod runtime;
+mod server_info;
+#[cfg(all(
+    feature = "tls",
+    any(feature = "runtime-tokio", feature = "rt-async-io")
+))]
+mod tls;
+mod trace;
+
+#[cfg(test)]
+mod test_utils;
+
+#[cfg(feature = "blocking")]
+pub mod blocking;
+
+pub mod protocol;
 
 /// Contains sonic channel error type and custom Result type for easy configure your functions.
 pub mod result;
 
 pub use channels::*;
-
-#[macro_use]
-extern crate lazy_static;
-extern crate regex;
+#[cfg(feature = "control")]
+pub use commands::ServerStats;
+#[cfg(feature = "search")]
+pub use commands::{ListRequest, QueryRequest, SuggestRequest};
+pub use ident::{Bucket, Collection, Dest, ObjDest, ObjectId};
+pub use lang::Lang;
+pub use options::ChannelOptions;
+#[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
+pub use pool::{PoolOptions, PooledChannel, SonicConnectionManager, SonicPool};
+#[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
+pub use reconnect::ReconnectPolicy;
+pub use server_info::{ServerInfo, ServerVersion};
+#[cfg(all(
+    feature = "tls",
+    any(feature = "runtime-tokio", feature = "rt-async-io")
+))]
+pub use tls::TlsConfig;
 
 #[cfg(test)]
 mod tests {
@@ -106,8 +190,11 @@ mod tests {
 
     #[test]
     fn format_channel_enums() {
+        #[cfg(feature = "search")]
         assert_eq!(format!("{}", ChannelMode::Search), String::from("search"));
+        #[cfg(feature = "ingest")]
         assert_eq!(format!("{}", ChannelMode::Ingest), String::from("ingest"));
+        #[cfg(feature = "control")]
         assert_eq!(format!("{}", ChannelMode::Control), String::from("control"));
     }
 
@@ -1,10 +1,38 @@
 macro_rules! init_command {
+    // Builds the command struct. Argument with alias `arg: Type as field`
+    // fills a field with another name. Fields that are not arguments are set
+    // after the arguments with `with { field: value }`.
+    (@command $cmd_name:ident { $($fields:tt)* }) => {
+        $cmd_name { $($fields)* }
+    };
+    (@command $cmd_name:ident { $($fields:tt)* }
+        $arg_name:ident as $field:ident => $arg_value:expr, $($rest:tt)*
+    ) => {
+        init_command!(@command $cmd_name { $($fields)* $field: $arg_value, } $($rest)*)
+    };
+    (@command $cmd_name:ident { $($fields:tt)* }
+        $arg_name:ident as $field:ident, $($rest:tt)*
+    ) => {
+        init_command!(@command $cmd_name { $($fields)* $field: $arg_name, } $($rest)*)
+    };
+    (@command $cmd_name:ident { $($fields:tt)* }
+        $arg_name:ident => $arg_value:expr, $($rest:tt)*
+    ) => {
+        init_command!(@command $cmd_name { $($fields)* $arg_name: $arg_value, } $($rest)*)
+    };
+    (@command $cmd_name:ident { $($fields:tt)* }
+        $arg_name:ident, $($rest:tt)*
+    ) => {
+        init_command!(@command $cmd_name { $($fields)* $arg_name, } $($rest)*)
+    };
+
     (
         $(#[$outer:meta])*
         use $cmd_name:ident
         for fn $fn_name:ident $(<$($lt:lifetime)+>)? (
-            $($arg_name:ident : $arg_type:ty $( => $arg_value:expr)?,)*
+            $($arg_name:ident : $arg_type:ty $(as $field:ident)? $( => $arg_value:expr)?,)*
         )
+        $(with { $($const_field:ident: $const_value:expr),* $(,)? })?
         $(;)?
     ) => {
         $(#[$outer])*
@@ -14,9 +42,57 @@ macro_rules! init_command {
         ) -> $crate::result::Result<
             <$cmd_name $(<$($lt)+>)? as $crate::commands::StreamCommand>::Response,
         > {
-            #[allow(clippy::needless_update)]
-            let command = $cmd_name { $($arg_name $(: $arg_value)?,)* ..Default::default() };
+            let command = init_command!(
+                @command $cmd_name { $($($const_field: $const_value,)*)? }
+                $($arg_name $(as $field)? $(=> $arg_value)?,)*
+            );
             self.stream().run_command(command).await
         }
     };
+
+    (
+        $(#[$outer:meta])*
+        blocking use $cmd_name:ident
+        for fn $fn_name:ident $(<$($lt:lifetime)+>)? (
+            $($arg_name:ident : $arg_type:ty $(as $field:ident)? $( => $arg_value:expr)?,)*
+        )
+        $(with { $($const_field:ident: $const_value:expr),* $(,)? })?
+        $(;)?
+    ) => {
+        $(#[$outer])*
+        pub fn $fn_name $(<$($lt)+>)? (
+            &self,
+            $($arg_name: $arg_type),*
+        ) -> $crate::result::Result<
+            <$cmd_name $(<$($lt)+>)? as $crate::commands::StreamCommand>::Response,
+        > {
+            let command = init_command!(
+                @command $cmd_name { $($($const_field: $const_value,)*)? }
+                $($arg_name $(as $field)? $(=> $arg_value)?,)*
+            );
+            self.stream().run_command(command)
+        }
+    };
+
+    (
+        $(#[$outer:meta])*
+        pipeline use $cmd_name:ident
+        for fn $fn_name:ident $(<$($lt:lifetime)+>)? (
+            $($arg_name:ident : $arg_type:ty $(as $field:ident)? $( => $arg_value:expr)?,)*
+        )
+        $(with { $($const_field:ident: $const_value:expr),* $(,)? })?
+        $(;)?
+    ) => {
+        $(#[$outer])*
+        pub fn $fn_name(
+            self,
+            $($arg_name: $arg_type),*
+        ) -> Self {
+            let command = init_command!(
+                @command $cmd_name { $($($const_field: $const_value,)*)? }
+                $($arg_name $(as $field)? $(=> $arg_value)?,)*
+            );
+            self.add(command)
+        }
+    };
 }
@@ -0,0 +1,119 @@
+#[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
+use crate::reconnect::ReconnectPolicy;
+#[cfg(all(
+    feature = "tls",
+    any(feature = "runtime-tokio", feature = "rt-async-io")
+))]
+use crate::tls::TlsConfig;
+use std::time::Duration;
+
+/// Connection options of sonic channels.
+///
+/// By default channels wait for the server forever. Set timeouts if you don't
+/// want to hang with a hung sonic node.
+///
+/// If read or write of a command times out, the channel is closed, because
+/// the rest of the response may still come from the server and confuse the
+/// next command. All next commands return `ErrorKind::WriteToStream` error,
+/// unless the channel is allowed to [reconnect](ChannelOptions::reconnect).
+///
+/// Note: Timeouts of async channels require enabling one of the runtime features.
+///
+/// ```rust
+/// use sonic_channel::ChannelOptions;
+/// use std::time::Duration;
+///
+/// let options = ChannelOptions::new()
+///     .connect_timeout(Duration::from_secs(1))
+///     .read_timeout(Duration::from_secs(5))
+///     .write_timeout(Duration::from_secs(5));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ChannelOptions {
+    pub(crate) connect_timeout: Option<Duration>,
+    pub(crate) read_timeout: Option<Duration>,
+    pub(crate) write_timeout: Option<Duration>,
+    #[cfg(all(
+        feature = "tls",
+        any(feature = "runtime-tokio", feature = "rt-async-io")
+    ))]
+    pub(crate) tls: Option<TlsConfig>,
+    #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
+    pub(crate) reconnect: Option<ReconnectPolicy>,
+    #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
+    pub(crate) keepalive: Option<Duration>,
+    #[cfg(feature = "tracing")]
+    pub(crate) trace_payloads: bool,
+}
+
+impl ChannelOptions {
+    /// Creates options without any timeouts.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets maximum time to open connection to each resolved address of the
+    /// server. TLS handshake should fit into this time too.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets maximum time to wait for a full response of one command.
+    pub fn read_timeout(mut self, timeout: Duration) -> Self {
+        self.read_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets maximum time to send one command to the server.
+    pub fn write_timeout(mut self, timeout: Duration) -> Self {
+        self.write_timeout = Some(timeout);
+        self
+    }
+
+    /// Connects to the server over TLS.
+    ///
+    /// Note: This method requires enabling the `tls` feature.
+    #[cfg(all(
+        feature = "tls",
+        any(feature = "runtime-tokio", feature = "rt-async-io")
+    ))]
+    pub fn tls(mut self, config: TlsConfig) -> Self {
+        self.tls = Some(config);
+        self
+    }
+
+    /// Reconnects to the server automatically after connection loss.
+    /// Channels that are started over custom transport can't reconnect
+    /// and ignore this option.
+    ///
+    /// Note: This method requires enabling one of the runtime features.
+    #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
+    pub fn reconnect(mut self, policy: ReconnectPolicy) -> Self {
+        self.reconnect = Some(policy);
+        self
+    }
+
+    /// Sends `PING` every time the channel is idle for the interval, so sonic
+    /// doesn't close it by `client_timeout`. If `PONG` doesn't come within the
+    /// same interval, the channel is marked unhealthy.
+    ///
+    /// Note: This method requires enabling one of the runtime features. With
+    /// `runtime-tokio` channel must be started inside the tokio runtime.
+    #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
+    pub fn keepalive(mut self, interval: Duration) -> Self {
+        self.keepalive = Some(interval);
+        self
+    }
+
+    /// Records quoted text of `PUSH`, `POP`, `QUERY` and `SUGGEST` in
+    /// command spans. Text is redacted by default, because it usually
+    /// contains user data. Passwords are redacted anyway.
+    ///
+    /// Note: This method requires enabling the `tracing` feature.
+    #[cfg(feature = "tracing")]
+    pub fn trace_payloads(mut self, enabled: bool) -> Self {
+        self.trace_payloads = enabled;
+        self
+    }
+}
@@ -0,0 +1,651 @@
+use crate::channels::SonicChannel;
+use crate::commands::{PingCommand, QuitCommand};
+use crate::options::ChannelOptions;
+use crate::result::*;
+use async_lock::{Semaphore, SemaphoreGuardArc};
+use std::collections::VecDeque;
+use std::fmt;
+use std::future::Future;
+use std::net::ToSocketAddrs;
+use std::ops::Deref;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::{Duration, Instant};
+
+type ChannelFuture<C> = Pin<Box<dyn Future<Output = Result<C>> + Send>>;
+
+/// Opens and checks channels of one type for connection pools.
+///
+/// It is used by [`SonicPool`] and also implements `bb8::ManageConnection`
+/// and `deadpool::managed::Manager` if you prefer one of these pools.
+///
+/// Note: This type requires enabling one of the runtime features. Integrations
+/// require enabling the `bb8` or `deadpool` feature.
+///
+/// ```rust,no_run
+/// # use sonic_channel::*;
+/// # fn main() -> result::Result<()> {
+/// # futures_lite::future::block_on(async {
+/// let manager = SonicConnectionManager::<SearchChannel>::new(
+///     "localhost:1491",
+///     "SecretPassword",
+/// );
+/// let channel = manager.connect().await?;
+/// manager.check(&channel).await?;
+/// # Ok(())
+/// # })
+/// # }
+/// ```
+pub struct SonicConnectionManager<C> {
+    open: Arc<dyn Fn() -> ChannelFuture<C> + Send + Sync>,
+}
+
+impl<C> Clone for SonicConnectionManager<C> {
+    fn clone(&self) -> Self {
+        SonicConnectionManager {
+            open: self.open.clone(),
+        }
+    }
+}
+
+impl<C> fmt::Debug for SonicConnectionManager<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SonicConnectionManager").finish()
+    }
+}
+
+impl<C> SonicConnectionManager<C>
+where
+    C: SonicChannel<Channel = C> + Send + Sync + 'static,
+{
+    /// Creates manager that opens channels with default options.
+    pub fn new<A, S>(addr: A, password: S) -> Self
+    where
+        A: ToSocketAddrs + Clone + Send + Sync + 'static,
+        S: ToString,
+    {
+        Self::with_options(addr, password, ChannelOptions::default())
+    }
+
+    /// Creates manager that opens channels with the given options.
+    pub fn with_options<A, S>(addr: A, password: S, options: ChannelOptions) -> Self
+    where
+        A: ToSocketAddrs + Clone + Send + Sync + 'static,
+        S: ToString,
+    {
+        let password = password.to_string();
+        SonicConnectionManager {
+            open: Arc::new(move || {
+                C::start_with_options(addr.clone(), password.clone(), options.clone())
+            }),
+        }
+    }
+
+    /// Opens new channel.
+    pub async fn connect(&self) -> Result<C> {
+        (self.open)().await
+    }
+
+    /// Checks that the channel is alive with `PING` command.
+    pub async fn check(&self, channel: &C) -> Result<()> {
+        channel.stream().run_command(PingCommand).await.map(|_| ())
+    }
+}
+
+#[cfg(feature = "bb8")]
+impl<C> bb8::ManageConnection for SonicConnectionManager<C>
+where
+    C: SonicChannel<Channel = C> + Send + Sync + 'static,
+{
+    type Connection = C;
+    type Error = Error;
+
+    async fn connect(&self) -> Result<C> {
+        SonicConnectionManager::connect(self).await
+    }
+
+    async fn is_valid(&self, channel: &mut C) -> Result<()> {
+        self.check(channel).await
+    }
+
+    fn has_broken(&self, channel: &mut C) -> bool {
+        !channel.is_healthy()
+    }
+}
+
+#[cfg(feature = "deadpool")]
+impl<C> deadpool::managed::Manager for SonicConnectionManager<C>
+where
+    C: SonicChannel<Channel = C> + Send + Sync + 'static,
+{
+    type Type = C;
+    type Error = Error;
+
+    async fn create(&self) -> Result<C> {
+        self.connect().await
+    }
+
+    async fn recycle(
+        &self,
+        channel: &mut C,
+        _metrics: &deadpool::managed::Metrics,
+    ) -> deadpool::managed::RecycleResult<Error> {
+        self.check(channel)
+            .await
+            .map_err(deadpool::managed::RecycleError::Backend)
+    }
+}
+
+/// Settings of [`SonicPool`].
+///
+/// By default pool keeps up to 10 channels, doesn't open channels in advance,
+/// waits for a free channel forever and never checks idle channels.
+///
+/// ```rust
+/// use sonic_channel::PoolOptions;
+/// use std::time::Duration;
+///
+/// let options = PoolOptions::new()
+///     .min_size(2)
+///     .max_size(16)
+///     .acquire_timeout(Duration::from_secs(1))
+///     .idle_timeout(Duration::from_secs(60))
+///     .health_check_interval(Duration::from_secs(30));
+/// ```
+#[derive(Debug, Clone)]
+pub struct PoolOptions {
+    min_size: usize,
+    max_size: usize,
+    acquire_timeout: Option<Duration>,
+    idle_timeout: Option<Duration>,
+    health_check_interval: Option<Duration>,
+    test_on_acquire: bool,
+}
+
+impl Default for PoolOptions {
+    fn default() -> Self {
+        PoolOptions {
+            min_size: 0,
+            max_size: 10,
+            acquire_timeout: None,
+            idle_timeout: None,
+            health_check_interval: None,
+            test_on_acquire: false,
+        }
+    }
+}
+
+impl PoolOptions {
+    /// Creates default pool options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets number of channels that pool opens on start and keeps open
+    /// even if they are idle. It can't be greater than the maximum size.
+    pub fn min_size(mut self, size: usize) -> Self {
+        self.min_size = size;
+        self
+    }
+
+    /// Sets maximum number of open channels.
+    pub fn max_size(mut self, size: usize) -> Self {
+        self.max_size = size.max(1);
+        self
+    }
+
+    /// Sets maximum time to wait for a free channel, including time to open
+    /// a new one.
+    pub fn acquire_timeout(mut self, timeout: Duration) -> Self {
+        self.acquire_timeout = Some(timeout);
+        self
+    }
+
+    /// Closes channels above the minimum size that are not used for this time.
+    pub fn idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Pings idle channels with this interval and replaces broken ones.
+    pub fn health_check_interval(mut self, interval: Duration) -> Self {
+        self.health_check_interval = Some(interval);
+        self
+    }
+
+    /// Pings every idle channel before it's taken from the pool.
+    pub fn test_on_acquire(mut self, test: bool) -> Self {
+        self.test_on_acquire = test;
+        self
+    }
+
+    /// Returns interval of background maintenance if it is required.
+    fn maintenance_interval(&self) -> Option<Duration> {
+        match (self.health_check_interval, self.idle_timeout) {
+            (Some(interval), Some(timeout)) => Some(interval.min(timeout)),
+            (interval, timeout) => interval.or(timeout),
+        }
+    }
+}
+
+/// Pool of sonic channels of one type.
+///
+/// Every channel runs one command at a time, so the pool lets many tasks run
+/// commands concurrently over several connections. The pool is cheap to clone,
+/// all clones share the same channels.
+///
+/// Note: This type requires enabling one of the runtime features.
+///
+/// ```rust,no_run
+/// # use sonic_channel::*;
+/// # fn main() -> result::Result<()> {
+/// # futures_lite::future::block_on(async {
+/// let manager = SonicConnectionManager::<SearchChannel>::new(
+///     "localhost:1491",
+///     "SecretPassword",
+/// );
+/// let pool = SonicPool::new(manager, PoolOptions::new().max_size(16)).await?;
+///
+/// let channel = pool.get().await?;
+/// let dest = Collection::new("collection")?.bucket("bucket")?;
+/// let objects = channel.query(QueryRequest::new(dest, "recipe")).await?;
+/// drop(channel); // returns the channel to the pool
+///
+/// pool.close().await;
+/// # Ok(())
+/// # })
+/// # }
+/// ```
+pub struct SonicPool<C> {
+    shared: Arc<Shared<C>>,
+}
+
+impl<C> Clone for SonicPool<C> {
+    fn clone(&self) -> Self {
+        SonicPool {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<C> fmt::Debug for SonicPool<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SonicPool")
+            .field("options", &self.shared.options)
+            .field("size", &self.shared.size)
+            .field("closed", &self.shared.closed)
+            .finish()
+    }
+}
+
+struct Shared<C> {
+    manager: SonicConnectionManager<C>,
+    options: PoolOptions,
+    idle: Mutex<VecDeque<Idle<C>>>,
+    permits: Arc<Semaphore>,
+    size: AtomicUsize,
+    closed: AtomicBool,
+}
+
+struct Idle<C> {
+    channel: C,
+    since: Instant,
+}
+
+impl<C> SonicPool<C>
+where
+    C: SonicChannel<Channel = C> + Send + Sync + 'static,
+{
+    /// Creates pool and opens the minimum number of channels. Returns
+    /// `ErrorKind::InvalidInput` if the minimum size is above the maximum.
+    pub async fn new(manager: SonicConnectionManager<C>, options: PoolOptions) -> Result<Self> {
+        if options.min_size > options.max_size {
+            return Err(Error::new(ErrorKind::InvalidInput(
+                "Pool min size is greater than max size",
+            )));
+        }
+
+        let shared = Arc::new(Shared {
+            manager,
+            permits: Arc::new(Semaphore::new(options.max_size)),
+            options,
+            idle: Mutex::default(),
+            size: AtomicUsize::new(0),
+            closed: AtomicBool::new(false),
+        });
+
+        while shared.size.load(Ordering::SeqCst) < shared.options.min_size {
+            let channel = shared.manager.connect().await?;
+            shared.size.fetch_add(1, Ordering::SeqCst);
+            shared.push_idle(channel);
+        }
+
+        if let Some(interval) = shared.options.maintenance_interval() {
+            crate::runtime::spawn(maintain(Arc::downgrade(&shared), interval));
+        }
+
+        Ok(SonicPool { shared })
+    }
+
+    /// Takes idle channel from the pool or opens a new one. If all channels
+    /// are busy, waits until one of them is returned.
+    ///
+    /// The channel returns to the pool when the guard is dropped. Broken
+    /// channels are closed instead.
+    pub async fn get(&self) -> Result<PooledChannel<C>> {
+        let acquire = async {
+            let permit = self.shared.permits.acquire_arc().await;
+            self.shared.checkout(permit).await
+        };
+
+        match self.shared.options.acquire_timeout {
+            Some(timeout) => crate::runtime::timeout(timeout, acquire)
+                .await
+                .unwrap_or_else(|| Err(Error::new(ErrorKind::PoolTimeout))),
+            None => acquire.await,
+        }
+    }
+
+    /// Returns number of open channels.
+    pub fn size(&self) -> usize {
+        self.shared.size.load(Ordering::SeqCst)
+    }
+
+    /// Returns number of channels that wait in the pool.
+    pub fn idle(&self) -> usize {
+        self.shared.idle.lock().unwrap().len()
+    }
+
+    /// Drains the pool gracefully. The pool stops giving out channels, waits
+    /// until all taken channels are returned and quits all of them.
+    pub async fn close(&self) {
+        self.shared.closed.store(true, Ordering::SeqCst);
+
+        // Every taken channel holds a permit, so we own all of them
+        // only when all channels are returned.
+        let mut permits = Vec::with_capacity(self.shared.options.max_size);
+        for _ in 0..self.shared.options.max_size {
+            permits.push(self.shared.permits.acquire_arc().await);
+        }
+
+        let idle: Vec<_> = self.shared.idle.lock().unwrap().drain(..).collect();
+        for Idle { channel, .. } in idle {
+            let _ = channel.stream().run_command(QuitCommand).await;
+            self.shared.size.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+}
+
+impl<C> Shared<C>
+where
+    C: SonicChannel<Channel = C> + Send + Sync + 'static,
+{
+    async fn checkout(self: &Arc<Self>, permit: SemaphoreGuardArc) -> Result<PooledChannel<C>> {
+        loop {
+            if self.closed.load(Ordering::SeqCst) {
+                return Err(Error::new(ErrorKind::PoolClosed));
+            }
+
+            // The last returned channel is used first, so channels above
+            // the minimum size stay idle and expire.
+            let idle = self.idle.lock().unwrap().pop_back();
+            let channel = match idle {
+                Some(Idle { channel, .. }) if !channel.is_healthy() => {
+                    self.size.fetch_sub(1, Ordering::SeqCst);
+                    continue;
+                }
+                Some(Idle { channel, .. }) if self.options.test_on_acquire => {
+                    if self.manager.check(&channel).await.is_err() {
+                        self.size.fetch_sub(1, Ordering::SeqCst);
+                        continue;
+                    }
+                    channel
+                }
+                Some(Idle { channel, .. }) => channel,
+                None => {
+                    let channel = self.manager.connect().await?;
+                    self.size.fetch_add(1, Ordering::SeqCst);
+                    channel
+                }
+            };
+
+            return Ok(PooledChannel {
+                channel: Some(channel),
+                shared: self.clone(),
+                _permit: permit,
+            });
+        }
+    }
+
+    fn push_idle(&self, channel: C) {
+        self.idle.lock().unwrap().push_back(Idle {
+            channel,
+            since: Instant::now(),
+        });
+    }
+
+    fn release(&self, channel: C) {
+        if channel.is_healthy() {
+            self.push_idle(channel);
+        } else {
+            self.size.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+
+    /// Closes channels that are expired at the given time, pings the rest
+    /// of idle channels and opens new ones up to the minimum size.
+    async fn maintain(&self, now: Instant) {
+        if let Some(timeout) = self.options.idle_timeout {
+            let mut idle = self.idle.lock().unwrap();
+            while self.size.load(Ordering::SeqCst) > self.options.min_size
+                && idle.front().is_some_and(|idle| idle.since + timeout <= now)
+            {
+                idle.pop_front();
+                self.size.fetch_sub(1, Ordering::SeqCst);
+            }
+        }
+
+        if self.options.health_check_interval.is_some() {
+            let count = self.idle.lock().unwrap().len();
+            for _ in 0..count {
+                // Checked channel is neither idle nor taken, so it holds
+                // a permit to keep the pool within the maximum size.
+                let _permit = match self.permits.try_acquire_arc() {
+                    Some(permit) => permit,
+                    None => break,
+                };
+                let idle = match self.idle.lock().unwrap().pop_front() {
+                    Some(idle) => idle,
+                    None => break,
+                };
+
+                if self.manager.check(&idle.channel).await.is_ok() {
+                    self.idle.lock().unwrap().push_back(idle);
+                } else {
+                    self.size.fetch_sub(1, Ordering::SeqCst);
+                }
+            }
+        }
+
+        while self.size.load(Ordering::SeqCst) < self.options.min_size {
+            let _permit = match self.permits.try_acquire_arc() {
+                Some(permit) => permit,
+                None => break,
+            };
+            match self.manager.connect().await {
+                Ok(channel) => {
+                    self.size.fetch_add(1, Ordering::SeqCst);
+                    self.push_idle(channel);
+                }
+                Err(_) => break,
+            }
+        }
+    }
+}
+
+/// Runs pool maintenance with the interval until the pool is dropped or closed.
+async fn maintain<C>(shared: Weak<Shared<C>>, interval: Duration)
+where
+    C: SonicChannel<Channel = C> + Send + Sync + 'static,
+{
+    loop {
+        crate::runtime::sleep(interval).await;
+        match shared.upgrade() {
+            Some(shared) if !shared.closed.load(Ordering::SeqCst) => {
+                shared.maintain(Instant::now()).await
+            }
+            _ => return,
+        }
+    }
+}
+
+/// Channel taken from [`SonicPool`]. It dereferences to the channel and
+/// returns it to the pool on drop.
+pub struct PooledChannel<C>
+where
+    C: SonicChannel<Channel = C> + Send + Sync + 'static,
+{
+    channel: Option<C>,
+    shared: Arc<Shared<C>>,
+    _permit: SemaphoreGuardArc,
+}
+
+impl<C> Deref for PooledChannel<C>
+where
+    C: SonicChannel<Channel = C> + Send + Sync + 'static,
+{
+    type Target = C;
+
+    fn deref(&self) -> &C {
+        self.channel
+            .as_ref()
+            .expect("Channel is taken only on drop")
+    }
+}
+
+impl<C> fmt::Debug for PooledChannel<C>
+where
+    C: SonicChannel<Channel = C> + Send + Sync + fmt::Debug + 'static,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("PooledChannel").field(&**self).finish()
+    }
+}
+
+impl<C> Drop for PooledChannel<C>
+where
+    C: SonicChannel<Channel = C> + Send + Sync + 'static,
+{
+    fn drop(&mut self) {
+        if let Some(channel) = self.channel.take() {
+            self.shared.release(channel);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "search"))]
+mod tests {
+    use super::*;
+    use crate::channels::SearchChannel;
+    use crate::runtime::block_on;
+    use crate::test_utils::{hanging_sonic_server, ponging_sonic_server};
+
+    #[test]
+    fn reuse_channels_within_max_size() {
+        let addr = ponging_sonic_server();
+        let manager = SonicConnectionManager::<SearchChannel>::new(addr, "secret");
+        let options = PoolOptions::new()
+            .min_size(1)
+            .max_size(2)
+            .acquire_timeout(Duration::from_millis(100));
+
+        block_on(async {
+            let pool = SonicPool::new(manager, options).await.unwrap();
+            assert_eq!((pool.size(), pool.idle()), (1, 1));
+
+            let first = pool.get().await.unwrap();
+            let second = pool.get().await.unwrap();
+            assert!(first.ping().await.unwrap());
+            assert!(second.ping().await.unwrap());
+            assert_eq!((pool.size(), pool.idle()), (2, 0));
+
+            let err = pool.get().await.unwrap_err();
+            assert!(matches!(err.kind, ErrorKind::PoolTimeout));
+
+            drop(first);
+            let third = pool.get().await.unwrap();
+            assert!(third.ping().await.unwrap());
+            assert_eq!(pool.size(), 2);
+
+            drop((second, third));
+            pool.close().await;
+            assert_eq!((pool.size(), pool.idle()), (0, 0));
+
+            let err = pool.get().await.unwrap_err();
+            assert!(matches!(err.kind, ErrorKind::PoolClosed));
+        });
+    }
+
+    #[test]
+    fn reject_min_size_above_max_size() {
+        let addr = ponging_sonic_server();
+        let manager = SonicConnectionManager::<SearchChannel>::new(addr, "secret");
+        let options = PoolOptions::new().min_size(5).max_size(2);
+
+        let err = block_on(SonicPool::new(manager, options)).unwrap_err();
+        assert!(matches!(err.kind, ErrorKind::InvalidInput(_)));
+    }
+
+    #[test]
+    fn close_idle_channels_after_timeout() {
+        let addr = ponging_sonic_server();
+        let manager = SonicConnectionManager::<SearchChannel>::new(addr, "secret");
+        // Background maintenance doesn't run during the test, it's run by hand.
+        let timeout = Duration::from_secs(60);
+        let options = PoolOptions::new()
+            .min_size(1)
+            .max_size(3)
+            .idle_timeout(timeout);
+
+        block_on(async {
+            let pool = SonicPool::new(manager, options).await.unwrap();
+            let first = pool.get().await.unwrap();
+            let second = pool.get().await.unwrap();
+            let third = pool.get().await.unwrap();
+            drop((first, second, third));
+            assert_eq!((pool.size(), pool.idle()), (3, 3));
+
+            pool.shared.maintain(Instant::now()).await;
+            assert_eq!((pool.size(), pool.idle()), (3, 3));
+
+            // Channels above the minimum size expire, the last one stays.
+            pool.shared.maintain(Instant::now() + timeout).await;
+            assert_eq!((pool.size(), pool.idle()), (1, 1));
+
+            let channel = pool.get().await.unwrap();
+            assert!(channel.ping().await.unwrap());
+        });
+    }
+
+    #[test]
+    fn discard_channels_that_fail_health_check() {
+        // The first ping gets `PONG`, the health check ping hangs.
+        let addr = hanging_sonic_server(1);
+        let channel_options = ChannelOptions::new().read_timeout(Duration::from_millis(50));
+        let manager =
+            SonicConnectionManager::<SearchChannel>::with_options(addr, "secret", channel_options);
+        let options = PoolOptions::new().health_check_interval(Duration::from_secs(60));
+
+        block_on(async {
+            let pool = SonicPool::new(manager, options).await.unwrap();
+            let channel = pool.get().await.unwrap();
+            assert!(channel.ping().await.unwrap());
+            drop(channel);
+            assert_eq!((pool.size(), pool.idle()), (1, 1));
+
+            pool.shared.maintain(Instant::now()).await;
+            assert_eq!((pool.size(), pool.idle()), (0, 0));
+        });
+    }
+}
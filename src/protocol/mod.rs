@@ -0,0 +1,44 @@
+//! Sonic channel protocol without any IO.
+//!
+//! Channels of this crate use these types to talk to the server, but they
+//! don't depend on sockets, so other transports, proxies and test tools can
+//! reuse the same grammar. [`Request`] encodes commands to protocol lines and
+//! [`Decoder`] turns received bytes into typed [`Response`]s.
+//!
+//! ```rust
+//! use sonic_channel::protocol::{Decoder, Request, Response};
+//!
+//! let request = Request::Query {
+//!     collection: "recipes",
+//!     bucket: "default",
+//!     terms: "beef",
+//!     limit: Some(10),
+//!     offset: None,
+//!     lang: None,
+//! };
+//! assert_eq!(request.encode().unwrap(), "QUERY recipes default \"beef\" LIMIT(10)\r\n");
+//!
+//! let mut decoder = Decoder::new();
+//! decoder.feed(b"PENDING Bt2m2gYa\r\nEVENT QUERY Bt2m2gYa recipe:295 rec");
+//! assert_eq!(
+//!     decoder.decode().unwrap(),
+//!     Some(Response::Pending("Bt2m2gYa"))
+//! );
+//! assert_eq!(decoder.decode().unwrap(), None);
+//!
+//! decoder.feed(b"ipe:296\r\n");
+//! assert!(matches!(
+//!     decoder.decode().unwrap(),
+//!     Some(Response::Event { objects, .. })
+//!         if objects.iter().eq(["recipe:295", "recipe:296"])
+//! ));
+//! ```
+
+mod request;
+mod response;
+
+#[cfg(feature = "ingest")]
+pub(crate) use request::{quoted_len, split_text};
+pub use request::{Request, TriggerAction};
+pub(crate) use response::is_last_line;
+pub use response::{Decoder, EventKind, Objects, Response, ResultFields};
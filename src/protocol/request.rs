@@ -0,0 +1,618 @@
+use crate::channels::ChannelMode;
+use crate::ident::{self, Bucket, Collection, ObjectId};
+use crate::result::*;
+use std::fmt::{self, Write};
+
+/// Action of the `TRIGGER` command in control mode.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum TriggerAction<'a> {
+    /// Consolidates indexed search data.
+    #[default]
+    Consolidate,
+    /// Backups KV and FST to the given path.
+    Backup(&'a str),
+    /// Restores KV and FST from the given path.
+    Restore(&'a str),
+}
+
+impl fmt::Display for TriggerAction<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TriggerAction::Consolidate => write!(f, "consolidate"),
+            TriggerAction::Backup(data) => write!(f, "backup {}", data),
+            TriggerAction::Restore(data) => write!(f, "restore {}", data),
+        }
+    }
+}
+
+/// Command that client sends to the server.
+///
+/// Text payloads are escaped by sonic quoting rules: `"` and line feeds are
+/// escaped with backslash, other control characters are replaced with spaces
+/// or removed. Sonic doesn't unescape backslash itself, so text that ends
+/// with backslash would escape the closing quote and is rejected.
+///
+/// `Display` formats the command without line terminator, use
+/// [`Request::encode`] to get the line that is sent to the server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Request<'a> {
+    /// `START <mode> <password>`
+    Start {
+        /// Mode of the channel.
+        mode: ChannelMode,
+        /// Password from the server config.
+        password: &'a str,
+    },
+    /// `QUIT`
+    Quit,
+    /// `PING`
+    Ping,
+    /// `QUERY <collection> <bucket> "<terms>" [LIMIT(<count>)] [OFFSET(<count>)] [LANG(<locale>)]`
+    Query {
+        /// Collection to search in.
+        collection: &'a str,
+        /// Bucket to search in.
+        bucket: &'a str,
+        /// Search terms.
+        terms: &'a str,
+        /// Maximum count of object ids in the response.
+        limit: Option<usize>,
+        /// Count of object ids to skip.
+        offset: Option<usize>,
+        /// Locale of the terms in ISO 639-3 code.
+        lang: Option<&'a str>,
+    },
+    /// `SUGGEST <collection> <bucket> "<word>" [LIMIT(<count>)]`
+    Suggest {
+        /// Collection to search in.
+        collection: &'a str,
+        /// Bucket to search in.
+        bucket: &'a str,
+        /// Beginning of the word.
+        word: &'a str,
+        /// Maximum count of words in the response.
+        limit: Option<usize>,
+    },
+    /// `LIST <collection> <bucket> [LIMIT(<count>)] [OFFSET(<count>)]`
+    List {
+        /// Collection to list words in.
+        collection: &'a str,
+        /// Bucket to list words in.
+        bucket: &'a str,
+        /// Maximum count of words in the response.
+        limit: Option<usize>,
+        /// Count of words to skip.
+        offset: Option<usize>,
+    },
+    /// `PUSH <collection> <bucket> <object> "<text>" [LANG(<locale>)]`
+    Push {
+        /// Collection of the object.
+        collection: &'a str,
+        /// Bucket of the object.
+        bucket: &'a str,
+        /// Object id.
+        object: &'a str,
+        /// Text to index.
+        text: &'a str,
+        /// Locale of the text in ISO 639-3 code.
+        locale: Option<&'a str>,
+    },
+    /// `POP <collection> <bucket> <object> "<text>"`
+    Pop {
+        /// Collection of the object.
+        collection: &'a str,
+        /// Bucket of the object.
+        bucket: &'a str,
+        /// Object id.
+        object: &'a str,
+        /// Text to remove from the index.
+        text: &'a str,
+    },
+    /// `COUNT <collection> [<bucket> [<object>]]`
+    ///
+    /// Object is ignored without bucket.
+    Count {
+        /// Collection to count buckets in.
+        collection: &'a str,
+        /// Bucket to count objects in.
+        bucket: Option<&'a str>,
+        /// Object to count words in.
+        object: Option<&'a str>,
+    },
+    /// `FLUSHC <collection>`
+    FlushCollection {
+        /// Collection to flush.
+        collection: &'a str,
+    },
+    /// `FLUSHB <collection> <bucket>`
+    FlushBucket {
+        /// Collection of the bucket.
+        collection: &'a str,
+        /// Bucket to flush.
+        bucket: &'a str,
+    },
+    /// `FLUSHO <collection> <bucket> <object>`
+    FlushObject {
+        /// Collection of the object.
+        collection: &'a str,
+        /// Bucket of the object.
+        bucket: &'a str,
+        /// Object to flush.
+        object: &'a str,
+    },
+    /// `TRIGGER <action> [<data>]`
+    Trigger(TriggerAction<'a>),
+    /// `INFO`
+    Info,
+    /// `HELP [<manual>]`
+    Help(Option<&'a str>),
+}
+
+impl Request<'_> {
+    /// Encodes request into the protocol line terminated by `\r\n`.
+    ///
+    /// Returns `ErrorKind::InvalidInput` if some payload can't be sent
+    /// safely: text has nothing but whitespace and control characters or ends
+    /// with backslash, locale is not a language code, password or trigger
+    /// data is not a single word.
+    pub fn encode(&self) -> Result<String> {
+        self.validate()?;
+        Ok(format!("{}\r\n", self))
+    }
+
+    fn validate(&self) -> Result<()> {
+        self.validate_names()?;
+        match self {
+            Request::Start { password, .. } => {
+                validate_word(password, "Password must be a single word")
+            }
+            Request::Suggest { word: text, .. } | Request::Pop { text, .. } => validate_text(text),
+            Request::Query {
+                terms: text,
+                lang: locale,
+                ..
+            }
+            | Request::Push { text, locale, .. } => {
+                validate_text(text)?;
+                match locale {
+                    Some(locale) => validate_locale(locale),
+                    None => Ok(()),
+                }
+            }
+            Request::Trigger(TriggerAction::Backup(data))
+            | Request::Trigger(TriggerAction::Restore(data)) => {
+                validate_word(data, "Trigger data must be a single word")
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Request<'_> {
+    /// Checks that collection, bucket and object are single words.
+    fn validate_names(&self) -> Result<()> {
+        let (collection, bucket, object) = match *self {
+            Request::Query {
+                collection, bucket, ..
+            }
+            | Request::Suggest {
+                collection, bucket, ..
+            }
+            | Request::List {
+                collection, bucket, ..
+            }
+            | Request::FlushBucket { collection, bucket } => (collection, Some(bucket), None),
+            Request::Push {
+                collection,
+                bucket,
+                object,
+                ..
+            }
+            | Request::Pop {
+                collection,
+                bucket,
+                object,
+                ..
+            }
+            | Request::FlushObject {
+                collection,
+                bucket,
+                object,
+            } => (collection, Some(bucket), Some(object)),
+            Request::Count {
+                collection,
+                bucket,
+                object,
+            } => (collection, bucket, object),
+            Request::FlushCollection { collection } => (collection, None, None),
+            Request::Start { .. }
+            | Request::Quit
+            | Request::Ping
+            | Request::Trigger(_)
+            | Request::Info => return Ok(()),
+            Request::Help(manual) => {
+                return match manual {
+                    Some(manual) => ident::validate(manual, "Manual must be a single word"),
+                    None => Ok(()),
+                }
+            }
+        };
+
+        Collection::new(collection)?;
+        if let Some(bucket) = bucket {
+            Bucket::new(bucket)?;
+        }
+        if let Some(object) = object {
+            ObjectId::new(object)?;
+        }
+        Ok(())
+    }
+}
+
+fn validate_text(text: &str) -> Result<()> {
+    if !has_visible_chars(text) {
+        Err(Error::new(ErrorKind::InvalidInput(
+            "Text has no visible characters",
+        )))
+    } else if ends_with_backslash(text) {
+        Err(Error::new(ErrorKind::InvalidInput(
+            "Text can't end with backslash",
+        )))
+    } else {
+        Ok(())
+    }
+}
+
+fn has_visible_chars(text: &str) -> bool {
+    text.chars().any(|c| !c.is_whitespace() && !c.is_control())
+}
+
+/// Checks the last character that [`Quoted`] writes, removed control
+/// characters are skipped.
+fn ends_with_backslash(text: &str) -> bool {
+    text.chars()
+        .rev()
+        .find(|c| !c.is_control() || c.is_whitespace())
+        == Some('\\')
+}
+
+fn validate_word(word: &str, reason: &'static str) -> Result<()> {
+    if word.is_empty() || word.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        Err(Error::new(ErrorKind::InvalidInput(reason)))
+    } else {
+        Ok(())
+    }
+}
+
+fn validate_locale(locale: &str) -> Result<()> {
+    if !locale.is_empty() && locale.chars().all(|c| c.is_ascii_alphabetic()) {
+        Ok(())
+    } else {
+        Err(Error::new(ErrorKind::InvalidInput(
+            "Locale must be ISO 639-3 code",
+        )))
+    }
+}
+
+/// Text payload that is written in quotes.
+struct Quoted<'a>(&'a str);
+
+impl fmt::Display for Quoted<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("\"")?;
+        for c in self.0.chars() {
+            match c {
+                '"' => f.write_str("\\\"")?,
+                '\n' => f.write_str("\\n")?,
+                c if c.is_control() && c.is_whitespace() => f.write_str(" ")?,
+                c if c.is_control() => {}
+                c => f.write_char(c)?,
+            }
+        }
+        f.write_str("\"")
+    }
+}
+
+/// Returns length of the character after escaping by [`Quoted`].
+#[cfg(feature = "ingest")]
+fn escaped_len(c: char) -> usize {
+    match c {
+        '"' | '\n' => 2,
+        c if c.is_control() && c.is_whitespace() => 1,
+        c if c.is_control() => 0,
+        c => c.len_utf8(),
+    }
+}
+
+/// Returns length of the text payload with quotes after escaping.
+#[cfg(feature = "ingest")]
+pub(crate) fn quoted_len(text: &str) -> usize {
+    text.chars().map(escaped_len).sum::<usize>() + 2
+}
+
+/// Splits text into parts which escaped length is at most `max_len` bytes.
+/// Text is split after whitespace if the part has any, otherwise in the
+/// middle of the word, but never inside UTF-8 character or right after
+/// backslash. Parts without visible characters are skipped.
+///
+/// Returns `None` if some character or run of backslashes before the next
+/// character doesn't fit even alone.
+#[cfg(feature = "ingest")]
+pub(crate) fn split_text(text: &str, max_len: usize) -> Option<Vec<&str>> {
+    let mut parts = Vec::new();
+    let mut start = 0;
+    let mut len = 0;
+    // Position after the last whitespace in the part and length before it.
+    let mut word_start = None;
+
+    for (i, c) in text.char_indices() {
+        let char_len = escaped_len(c);
+        if char_len > max_len {
+            return None;
+        }
+        if len + char_len > max_len {
+            if let Some((pos, len_before)) = word_start.take() {
+                parts.push(&text[start..pos]);
+                start = pos;
+                len -= len_before;
+            }
+            if len + char_len > max_len {
+                // Backslash at the end of the part would escape its closing
+                // quote, so it goes to the next part.
+                let mut cut = i;
+                while ends_with_backslash(&text[start..cut]) {
+                    cut = start + text[start..cut].rfind('\\')?;
+                }
+                if cut == start {
+                    return None;
+                }
+                parts.push(&text[start..cut]);
+                start = cut;
+                len = text[cut..i].chars().map(escaped_len).sum();
+                if len + char_len > max_len {
+                    return None;
+                }
+            }
+        }
+        len += char_len;
+        if c.is_whitespace() {
+            word_start = Some((i + c.len_utf8(), len));
+        }
+    }
+    parts.push(&text[start..]);
+
+    parts.retain(|part| has_visible_chars(part));
+    Some(parts)
+}
+
+impl fmt::Display for Request<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Request::Start { mode, password } => write!(f, "START {} {}", mode, password),
+            Request::Quit => write!(f, "QUIT"),
+            Request::Ping => write!(f, "PING"),
+            Request::Query {
+                collection,
+                bucket,
+                terms,
+                limit,
+                offset,
+                lang,
+            } => {
+                write!(f, "QUERY {} {} {}", collection, bucket, Quoted(terms))?;
+                if let Some(limit) = limit {
+                    write!(f, " LIMIT({})", limit)?;
+                }
+                if let Some(offset) = offset {
+                    write!(f, " OFFSET({})", offset)?;
+                }
+                if let Some(lang) = lang {
+                    write!(f, " LANG({})", lang)?;
+                }
+                Ok(())
+            }
+            Request::Suggest {
+                collection,
+                bucket,
+                word,
+                limit,
+            } => {
+                write!(f, "SUGGEST {} {} {}", collection, bucket, Quoted(word))?;
+                if let Some(limit) = limit {
+                    write!(f, " LIMIT({})", limit)?;
+                }
+                Ok(())
+            }
+            Request::List {
+                collection,
+                bucket,
+                limit,
+                offset,
+            } => {
+                write!(f, "LIST {} {}", collection, bucket)?;
+                if let Some(limit) = limit {
+                    write!(f, " LIMIT({})", limit)?;
+                }
+                if let Some(offset) = offset {
+                    write!(f, " OFFSET({})", offset)?;
+                }
+                Ok(())
+            }
+            Request::Push {
+                collection,
+                bucket,
+                object,
+                text,
+                locale,
+            } => {
+                write!(
+                    f,
+                    "PUSH {} {} {} {}",
+                    collection,
+                    bucket,
+                    object,
+                    Quoted(text)
+                )?;
+                if let Some(locale) = locale {
+                    write!(f, " LANG({})", locale)?;
+                }
+                Ok(())
+            }
+            Request::Pop {
+                collection,
+                bucket,
+                object,
+                text,
+            } => write!(
+                f,
+                "POP {} {} {} {}",
+                collection,
+                bucket,
+                object,
+                Quoted(text)
+            ),
+            Request::Count {
+                collection,
+                bucket,
+                object,
+            } => {
+                write!(f, "COUNT {}", collection)?;
+                if let Some(bucket) = bucket {
+                    write!(f, " {}", bucket)?;
+                    if let Some(object) = object {
+                        write!(f, " {}", object)?;
+                    }
+                }
+                Ok(())
+            }
+            Request::FlushCollection { collection } => write!(f, "FLUSHC {}", collection),
+            Request::FlushBucket { collection, bucket } => {
+                write!(f, "FLUSHB {} {}", collection, bucket)
+            }
+            Request::FlushObject {
+                collection,
+                bucket,
+                object,
+            } => write!(f, "FLUSHO {} {} {}", collection, bucket, object),
+            Request::Trigger(action) => write!(f, "TRIGGER {}", action),
+            Request::Info => write!(f, "INFO"),
+            Request::Help(None) => write!(f, "HELP"),
+            Request::Help(Some(manual)) => write!(f, "HELP {}", manual),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::any_mode;
+
+    #[test]
+    fn escape_text_payloads() {
+        let request = Request::Push {
+            collection: "messages",
+            bucket: "default",
+            object: "msg:1",
+            text: "say \"hi\"\r\nFLUSHC messages\t\u{0}!",
+            locale: Some("eng"),
+        };
+        assert_eq!(
+            request.encode().unwrap(),
+            "PUSH messages default msg:1 \"say \\\"hi\\\" \\nFLUSHC messages !\" LANG(eng)\r\n"
+        );
+
+        // Backslash in the middle of the text doesn't escape the closing quote.
+        let request = Request::Query {
+            collection: "files",
+            bucket: "default",
+            terms: "C:\\ drive",
+            limit: Some(10),
+            offset: None,
+            lang: None,
+        };
+        assert_eq!(
+            request.encode().unwrap(),
+            "QUERY files default \"C:\\ drive\" LIMIT(10)\r\n"
+        );
+
+        for request in [
+            Request::Query {
+                collection: "messages",
+                bucket: "default",
+                terms: " \r\n\u{7}",
+                limit: None,
+                offset: None,
+                lang: None,
+            },
+            Request::Push {
+                collection: "messages",
+                bucket: "default",
+                object: "msg:1",
+                text: "hi",
+                locale: Some("eng) LANG(rus"),
+            },
+            Request::Trigger(TriggerAction::Backup("backup\r\nQUIT")),
+            Request::Start {
+                mode: any_mode(),
+                password: "secret\r\nTRIGGER consolidate",
+            },
+            Request::Start {
+                mode: any_mode(),
+                password: "two words",
+            },
+            Request::Query {
+                collection: "files",
+                bucket: "default",
+                terms: "C:\\",
+                limit: Some(10),
+                offset: None,
+                lang: None,
+            },
+            Request::Push {
+                collection: "files",
+                bucket: "default",
+                object: "file:1",
+                text: "path\\\u{0}",
+                locale: None,
+            },
+        ] {
+            let err = request.encode().unwrap_err();
+            assert!(matches!(err.kind, ErrorKind::InvalidInput(_)), "{}", err);
+        }
+    }
+
+    #[test]
+    fn encode_query_options() {
+        let request = Request::Query {
+            collection: "messages",
+            bucket: "default",
+            terms: "hello",
+            limit: Some(10),
+            offset: Some(20),
+            lang: Some("eng"),
+        };
+        assert_eq!(
+            request.encode().unwrap(),
+            "QUERY messages default \"hello\" LIMIT(10) OFFSET(20) LANG(eng)\r\n"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "ingest")]
+    fn split_text_at_word_boundaries() {
+        assert_eq!(
+            split_text("beef teriyaki  skewers", 10),
+            Some(vec!["beef ", "teriyaki  ", "skewers"])
+        );
+        assert_eq!(split_text("грен ки", 5), Some(vec!["гр", "ен ", "ки"]));
+        assert_eq!(split_text("\"a\"", 2), Some(vec!["\"", "a", "\""]));
+        assert_eq!(split_text("a\"", 1), None);
+        assert_eq!(split_text("path\\ab", 5), Some(vec!["path", "\\ab"]));
+        assert_eq!(split_text("a\\\\\\b", 3), None);
+
+        for part in split_text("Гренки с жареным картофелем и сыром", 12).unwrap()
+        {
+            assert!(quoted_len(part) <= 12 + 2, "{:?}", part);
+        }
+    }
+}
@@ -0,0 +1,336 @@
+use crate::result::*;
+
+/// Kind of the `EVENT` response.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum EventKind {
+    /// Result of `QUERY` with object ids.
+    Query,
+    /// Result of `SUGGEST` with words.
+    Suggest,
+    /// Result of `LIST` with words.
+    List,
+}
+
+/// Object ids or words of the `EVENT` response. They are borrowed from
+/// the line and split lazily.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Objects<'a>(&'a str);
+
+impl<'a> Objects<'a> {
+    /// Returns iterator over objects.
+    pub fn iter(&self) -> std::str::SplitWhitespace<'a> {
+        self.0.split_whitespace()
+    }
+
+    /// Returns `true` if the event has no objects.
+    pub fn is_empty(&self) -> bool {
+        self.0.trim().is_empty()
+    }
+
+    /// Returns objects as they were sent by the server.
+    pub fn as_str(&self) -> &'a str {
+        self.0
+    }
+}
+
+impl<'a> IntoIterator for Objects<'a> {
+    type Item = &'a str;
+    type IntoIter = std::str::SplitWhitespace<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// `<key>(<value>)` pairs of the `RESULT` response to `INFO` and `HELP`.
+/// Values may contain spaces, but not parentheses. They are borrowed from
+/// the line and split lazily.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ResultFields<'a>(&'a str);
+
+impl<'a> ResultFields<'a> {
+    /// Returns iterator over keys and values.
+    pub fn iter(&self) -> impl Iterator<Item = (&'a str, &'a str)> {
+        let mut rest = self.0;
+        std::iter::from_fn(move || {
+            let (field, tail) = split_field(rest)?;
+            rest = tail;
+            Some(field)
+        })
+    }
+
+    /// Returns value of the key if the server sent it.
+    pub fn get(&self, key: &str) -> Option<&'a str> {
+        self.iter().find(|(k, _)| *k == key).map(|(_, value)| value)
+    }
+
+    /// Returns fields as they were sent by the server.
+    pub fn as_str(&self) -> &'a str {
+        self.0
+    }
+}
+
+/// Splits the first `<key>(<value>)` field from the rest of the line.
+fn split_field(line: &str) -> Option<((&str, &str), &str)> {
+    let line = line.trim_start();
+    let (key, rest) = line.split_once('(')?;
+    let (value, rest) = rest.split_once(')')?;
+    if key.is_empty() || key.contains(char::is_whitespace) {
+        return None;
+    }
+    Some(((key, value), rest))
+}
+
+/// Checks that the line has nothing but `<key>(<value>)` fields.
+fn is_fields(mut line: &str) -> bool {
+    while !line.trim().is_empty() {
+        match split_field(line) {
+            Some((_, rest)) if rest.is_empty() || rest.starts_with(' ') => line = rest,
+            _ => return false,
+        }
+    }
+    true
+}
+
+/// Line that server sends to the client. All strings are borrowed from the
+/// line, so parsing doesn't allocate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Response<'a> {
+    /// `CONNECTED <banner>` is sent right after connection is opened.
+    Connected(&'a str),
+    /// `STARTED <mode> protocol(<version>) buffer(<size>)` is the answer to
+    /// `START`.
+    Started {
+        /// Mode of the channel.
+        mode: &'a str,
+        /// Version of the sonic protocol.
+        protocol_version: usize,
+        /// Maximum size of one request in bytes.
+        max_buffer_size: usize,
+    },
+    /// `PENDING <id>` means that the event with this id comes later.
+    Pending(&'a str),
+    /// `EVENT <kind> <id> <objects>...`
+    Event {
+        /// Command that the event answers.
+        kind: EventKind,
+        /// Id from the `PENDING` response.
+        id: &'a str,
+        /// Object ids or words.
+        objects: Objects<'a>,
+    },
+    /// `OK`
+    Ok,
+    /// `PONG`
+    Pong,
+    /// `RESULT <count>`
+    Result(usize),
+    /// `RESULT <key>(<value>)...` is the answer to `INFO` and `HELP`.
+    Fields(ResultFields<'a>),
+    /// `ENDED <reason>` is sent before server closes connection.
+    Ended(&'a str),
+    /// `ERR <reason>`
+    Err(&'a str),
+}
+
+impl<'a> Response<'a> {
+    /// Parses one line of the server response. Line terminator is optional.
+    pub fn parse(line: &'a str) -> Result<Self> {
+        let line = line.trim_end_matches(&['\r', '\n'][..]);
+        let (head, rest) = line.split_once(' ').unwrap_or((line, ""));
+        let wrong = || Error::new(ErrorKind::WrongSonicResponse).with_line(line);
+
+        let response = match head {
+            "CONNECTED" => Response::Connected(rest),
+            "STARTED" => {
+                let mut params = rest.split(' ');
+                let mode = params.next().filter(|mode| !mode.is_empty());
+                let protocol_version = params.next().and_then(|p| parse_param(p, "protocol"));
+                let max_buffer_size = params.next().and_then(|p| parse_param(p, "buffer"));
+                match (mode, protocol_version, max_buffer_size) {
+                    (Some(mode), Some(protocol_version), Some(max_buffer_size)) => {
+                        Response::Started {
+                            mode,
+                            protocol_version,
+                            max_buffer_size,
+                        }
+                    }
+                    _ => return Err(wrong()),
+                }
+            }
+            "PENDING" if !rest.is_empty() => Response::Pending(rest),
+            "EVENT" => {
+                let (kind, rest) = rest.split_once(' ').unwrap_or((rest, ""));
+                let kind = match kind {
+                    "QUERY" => EventKind::Query,
+                    "SUGGEST" => EventKind::Suggest,
+                    "LIST" => EventKind::List,
+                    _ => return Err(wrong()),
+                };
+                let (id, objects) = rest.split_once(' ').unwrap_or((rest, ""));
+                if id.is_empty() {
+                    return Err(wrong());
+                }
+                Response::Event {
+                    kind,
+                    id,
+                    objects: Objects(objects),
+                }
+            }
+            "OK" if rest.is_empty() => Response::Ok,
+            "PONG" if rest.is_empty() => Response::Pong,
+            "RESULT" => match rest.parse() {
+                Ok(count) => Response::Result(count),
+                Err(_) if !rest.is_empty() && is_fields(rest) => {
+                    Response::Fields(ResultFields(rest))
+                }
+                Err(_) => return Err(wrong()),
+            },
+            "ENDED" => Response::Ended(rest),
+            "ERR" => Response::Err(rest),
+            _ => return Err(wrong()),
+        };
+        Ok(response)
+    }
+}
+
+/// Returns `true` if the line ends the response even if the command expects
+/// more lines: `ERR` and `ENDED` come instead of the whole answer.
+pub(crate) fn is_last_line(line: &str) -> bool {
+    line.starts_with("ERR ") || line.starts_with("ENDED ")
+}
+
+/// Parses value of `name(<value>)` parameter.
+fn parse_param(param: &str, name: &str) -> Option<usize> {
+    param
+        .strip_prefix(name)?
+        .strip_prefix('(')?
+        .strip_suffix(')')?
+        .parse()
+        .ok()
+}
+
+/// Splits received bytes into lines and parses them into responses.
+///
+/// Bytes may come in chunks of any size: incomplete line stays in the
+/// decoder until the rest of it is fed. Decoded response borrows the line
+/// from the decoder buffer until the next call.
+#[derive(Debug, Default)]
+pub struct Decoder {
+    buffer: Vec<u8>,
+    consumed: usize,
+}
+
+impl Decoder {
+    /// Creates decoder with empty buffer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends received bytes to the buffer.
+    pub fn feed(&mut self, bytes: &[u8]) {
+        self.compact();
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Returns the next response, or `None` if there is no complete line in
+    /// the buffer yet. The line is consumed even if it cannot be parsed.
+    pub fn decode(&mut self) -> Result<Option<Response<'_>>> {
+        self.compact();
+        let end = match self.buffer.iter().position(|&byte| byte == b'\n') {
+            Some(end) => end,
+            None => return Ok(None),
+        };
+        self.consumed = end + 1;
+        let line = std::str::from_utf8(&self.buffer[..self.consumed])
+            .map_err(|err| Error::new(ErrorKind::WrongSonicResponse).with_source(err))?;
+        Response::parse(line).map(Some)
+    }
+
+    /// Returns count of bytes that are not decoded yet.
+    pub fn buffered(&self) -> usize {
+        self.buffer.len() - self.consumed
+    }
+
+    /// Drops the line that was returned by the previous decode.
+    fn compact(&mut self) {
+        self.buffer.drain(..self.consumed);
+        self.consumed = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_all_responses() {
+        for (line, response) in [
+            (
+                "CONNECTED <sonic-server v1.3.0>\r\n",
+                Response::Connected("<sonic-server v1.3.0>"),
+            ),
+            (
+                "STARTED search protocol(1) buffer(20000)\r\n",
+                Response::Started {
+                    mode: "search",
+                    protocol_version: 1,
+                    max_buffer_size: 20000,
+                },
+            ),
+            ("PENDING q1\r\n", Response::Pending("q1")),
+            (
+                "EVENT SUGGEST q1 beef beer\r\n",
+                Response::Event {
+                    kind: EventKind::Suggest,
+                    id: "q1",
+                    objects: Objects("beef beer"),
+                },
+            ),
+            (
+                "EVENT QUERY q1\r\n",
+                Response::Event {
+                    kind: EventKind::Query,
+                    id: "q1",
+                    objects: Objects(""),
+                },
+            ),
+            ("OK\r\n", Response::Ok),
+            ("PONG", Response::Pong),
+            ("RESULT 42\r\n", Response::Result(42)),
+            (
+                "RESULT uptime(24) clients_connected(1)\r\n",
+                Response::Fields(ResultFields("uptime(24) clients_connected(1)")),
+            ),
+            (
+                "RESULT commands(QUERY, SUGGEST, LIST, PING, HELP, QUIT)\r\n",
+                Response::Fields(ResultFields(
+                    "commands(QUERY, SUGGEST, LIST, PING, HELP, QUIT)",
+                )),
+            ),
+            ("ENDED quit\r\n", Response::Ended("quit")),
+            (
+                "ERR invalid_format(QUERY <collection> <bucket> \"<terms>\")\r\n",
+                Response::Err("invalid_format(QUERY <collection> <bucket> \"<terms>\")"),
+            ),
+        ] {
+            assert_eq!(Response::parse(line).unwrap(), response, "{}", line);
+        }
+
+        for line in [
+            "",
+            "HELLO\r\n",
+            "OK computer\r\n",
+            "PENDING\r\n",
+            "EVENT PUSH q1\r\n",
+            "RESULT many\r\n",
+            "RESULT uptime(24) (1)\r\n",
+            "RESULT uptime(24)clients(1)\r\n",
+            "STARTED search protocol(x) buffer(20000)\r\n",
+        ] {
+            assert!(Response::parse(line).is_err(), "{}", line);
+        }
+    }
+}
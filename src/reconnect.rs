@@ -0,0 +1,128 @@
+use crate::channels::Transport;
+use crate::result::Result;
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+
+/// Policy of automatic reconnection of async channels.
+///
+/// When the server closes connection, restarts or answers `ENDED`, the channel
+/// opens a new connection and runs `START` again before the next command.
+/// Attempts are delayed with exponential backoff and random jitter, so many
+/// clients don't reconnect to the restarted server at the same moment.
+///
+/// The command that was interrupted by connection loss is sent again only if
+/// it is safe: it's a read-only command like `PING`, `QUERY`, `SUGGEST` or
+/// `COUNT`, or the server answered `ENDED` and didn't run it at all. Other
+/// commands return an error, and the next command reconnects.
+///
+/// Note: This type requires enabling one of the runtime features.
+///
+/// ```rust
+/// use sonic_channel::{ChannelOptions, ReconnectPolicy};
+/// use std::time::Duration;
+///
+/// let options = ChannelOptions::new().reconnect(
+///     ReconnectPolicy::new()
+///         .max_attempts(10)
+///         .max_delay(Duration::from_secs(5)),
+/// );
+/// ```
+#[derive(Debug, Clone)]
+pub struct ReconnectPolicy {
+    max_attempts: usize,
+    initial_delay: Duration,
+    max_delay: Duration,
+}
+
+impl Default for ReconnectPolicy {
+    fn default() -> Self {
+        ReconnectPolicy {
+            max_attempts: 5,
+            initial_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(10),
+        }
+    }
+}
+
+impl ReconnectPolicy {
+    /// Creates policy with 5 attempts and delays from 100ms up to 10s.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets how many times the channel tries to reconnect before it gives up
+    /// and returns the error of the last attempt.
+    pub fn max_attempts(mut self, attempts: usize) -> Self {
+        self.max_attempts = attempts.max(1);
+        self
+    }
+
+    /// Sets delay before the second attempt. The first attempt is made
+    /// immediately, each next delay is twice as long as the previous one.
+    pub fn initial_delay(mut self, delay: Duration) -> Self {
+        self.initial_delay = delay;
+        self
+    }
+
+    /// Sets upper limit of delay between attempts.
+    pub fn max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    /// Returns delay before attempt with the given number, starting from zero.
+    /// Jitter takes up to a half of the delay.
+    pub(crate) fn delay(&self, attempt: usize) -> Duration {
+        if attempt == 0 {
+            return Duration::ZERO;
+        }
+
+        let factor = 1u32 << (attempt - 1).min(31);
+        let delay = self
+            .initial_delay
+            .checked_mul(factor)
+            .map_or(self.max_delay, |delay| delay.min(self.max_delay));
+        let half = delay / 2;
+        half + half.mul_f64(fastrand::f64())
+    }
+
+    pub(crate) fn attempts(&self) -> usize {
+        self.max_attempts
+    }
+}
+
+pub(crate) type TransportFuture = Pin<Box<dyn Future<Output = Result<Box<dyn Transport>>> + Send>>;
+
+/// Everything that is required to open connection again.
+pub(crate) struct Reconnect {
+    pub(crate) connect: Box<dyn Fn() -> TransportFuture + Send + Sync>,
+    pub(crate) password: String,
+    pub(crate) policy: ReconnectPolicy,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grow_delay_exponentially_up_to_max() {
+        let policy = ReconnectPolicy::new()
+            .initial_delay(Duration::from_millis(100))
+            .max_delay(Duration::from_secs(1));
+
+        assert_eq!(policy.delay(0), Duration::ZERO);
+        for (attempt, full) in [
+            (1, 100),
+            (2, 200),
+            (3, 400),
+            (4, 800),
+            (5, 1000),
+            (60, 1000),
+        ] {
+            let delay = policy.delay(attempt);
+            let full = Duration::from_millis(full);
+            assert!(delay >= full / 2 && delay <= full, "{:?}", delay);
+        }
+    }
+}
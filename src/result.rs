@@ -8,12 +8,23 @@ pub type Result<T> = std::result::Result<T, Error>;
 /// Wrap for sonic channel error kind. This type has std::error::Error
 /// implementation and you can use boxed trait for catch other errors
 /// like this.
+///
+/// Error keeps the IO error that caused it as [`source`](StdError::source)
+/// and the server line that the client couldn't accept.
 #[derive(Debug)]
 pub struct Error {
-    kind: ErrorKind,
+    pub(crate) kind: ErrorKind,
+    source: Option<Box<dyn StdError + Send + Sync>>,
+    line: Option<String>,
 }
 
-impl StdError for Error {}
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        self.source
+            .as_ref()
+            .map(|source| source.as_ref() as &(dyn StdError + 'static))
+    }
+}
 
 impl Error {
     /// Creates new Error with sonic channel error kind
@@ -24,12 +35,176 @@ impl Error {
     /// let err = Error::new(ErrorKind::ConnectToServer);
     /// ```
     pub fn new(kind: ErrorKind) -> Self {
-        Error { kind }
+        Error {
+            kind,
+            source: None,
+            line: None,
+        }
+    }
+
+    /// Returns kind of the error.
+    ///
+    /// ```rust
+    /// use sonic_channel::result::*;
+    ///
+    /// let err = Error::new(ErrorKind::Timeout);
+    /// assert!(matches!(err.kind(), ErrorKind::Timeout));
+    /// ```
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+
+    /// Returns the line of the server response that caused the error,
+    /// without line terminator.
+    pub fn server_line(&self) -> Option<&str> {
+        self.line.as_deref()
+    }
+
+    /// Returns `true` if the server didn't answer in time or no channel
+    /// became free in the pool.
+    pub fn is_timeout(&self) -> bool {
+        match self.kind {
+            ErrorKind::Timeout => true,
+            #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
+            ErrorKind::PoolTimeout => true,
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if the connection to the server failed or was closed.
+    /// The channel is unhealthy after such error.
+    ///
+    /// Timeouts are not included, check them with [`Error::is_timeout`].
+    /// Single connection channels are closed after a timeout as well, only
+    /// multiplexed channel keeps the connection after a read timeout.
+    pub fn is_connection_error(&self) -> bool {
+        matches!(
+            self.kind,
+            ErrorKind::ConnectToServer
+                | ErrorKind::WriteToStream
+                | ErrorKind::ReadStream
+                | ErrorKind::ConnectionEnded(_)
+        )
+    }
+
+    /// Returns `true` if the same command may succeed later, on the new
+    /// connection or when the server is less busy. Errors in the command
+    /// itself are not retryable.
+    pub fn is_retryable(&self) -> bool {
+        self.is_connection_error()
+            || self.is_timeout()
+            || matches!(
+                self.kind,
+                ErrorKind::Server {
+                    code: ServerErrorCode::InternalError | ServerErrorCode::ShuttingDown,
+                    ..
+                }
+            )
+    }
+
+    /// Keeps the error that caused this one.
+    pub(crate) fn with_source<E>(mut self, source: E) -> Self
+    where
+        E: Into<Box<dyn StdError + Send + Sync>>,
+    {
+        self.source = Some(source.into());
+        self
+    }
+
+    /// Keeps the server line that caused the error.
+    pub(crate) fn with_line(mut self, line: &str) -> Self {
+        self.line = Some(line.trim_end_matches(&['\r', '\n'][..]).to_owned());
+        self
+    }
+
+    /// Creates error from the reason of `ERR <code>(<detail>)` line.
+    pub(crate) fn server(reason: &str) -> Self {
+        let (code, detail) = match reason.split_once('(') {
+            Some((code, detail)) => (code, Some(detail.strip_suffix(')').unwrap_or(detail))),
+            None => (reason, None),
+        };
+        Error::new(ErrorKind::Server {
+            code: ServerErrorCode::from_code(code),
+            detail: detail.map(str::to_owned),
+        })
+    }
+}
+
+/// Code of the `ERR` response. Sonic sends it when it rejects the command,
+/// the connection stays open.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ServerErrorCode {
+    /// Command is not available in the channel mode.
+    UnknownCommand,
+    /// Command is not recognized at all.
+    NotRecognized,
+    /// Object or bucket doesn't exist.
+    NotFound,
+    /// Query can't be run.
+    QueryError,
+    /// Server failed to run the command.
+    InternalError,
+    /// Server is shutting down and doesn't accept commands.
+    ShuttingDown,
+    /// Command is rejected by server policy, detail has the reason.
+    PolicyReject,
+    /// Command has wrong format, detail has the expected one.
+    InvalidFormat,
+    /// Command has unknown meta key like `LIMIT`.
+    InvalidMetaKey,
+    /// Command has wrong value of the meta key.
+    InvalidMetaValue,
+    /// Code that this crate doesn't know yet.
+    Other(String),
+}
+
+impl ServerErrorCode {
+    /// Returns code by its name in the `ERR` response.
+    pub fn from_code(code: &str) -> Self {
+        match code {
+            "unknown_command" => ServerErrorCode::UnknownCommand,
+            "not_recognized" => ServerErrorCode::NotRecognized,
+            "not_found" => ServerErrorCode::NotFound,
+            "query_error" => ServerErrorCode::QueryError,
+            "internal_error" => ServerErrorCode::InternalError,
+            "shutting_down" => ServerErrorCode::ShuttingDown,
+            "policy_reject" => ServerErrorCode::PolicyReject,
+            "invalid_format" => ServerErrorCode::InvalidFormat,
+            "invalid_meta_key" => ServerErrorCode::InvalidMetaKey,
+            "invalid_meta_value" => ServerErrorCode::InvalidMetaValue,
+            code => ServerErrorCode::Other(code.to_owned()),
+        }
+    }
+
+    /// Returns name of the code as the server sends it.
+    pub fn code(&self) -> &str {
+        match self {
+            ServerErrorCode::UnknownCommand => "unknown_command",
+            ServerErrorCode::NotRecognized => "not_recognized",
+            ServerErrorCode::NotFound => "not_found",
+            ServerErrorCode::QueryError => "query_error",
+            ServerErrorCode::InternalError => "internal_error",
+            ServerErrorCode::ShuttingDown => "shutting_down",
+            ServerErrorCode::PolicyReject => "policy_reject",
+            ServerErrorCode::InvalidFormat => "invalid_format",
+            ServerErrorCode::InvalidMetaKey => "invalid_meta_key",
+            ServerErrorCode::InvalidMetaValue => "invalid_meta_value",
+            ServerErrorCode::Other(code) => code,
+        }
     }
 }
 
-/// All error kinds that you can see in sonic-channel crate.
+impl fmt::Display for ServerErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+/// All error kinds that you can see in sonic-channel crate. New kinds may be
+/// added in minor releases, so match them with a wildcard arm.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum ErrorKind {
     /// Cannot connect to the sonic search backend.
     ConnectToServer,
@@ -46,20 +221,68 @@ pub enum ErrorKind {
     /// Cannot run command in current mode.
     RunCommand,
 
-    /// Error in query response with additional message.
+    /// Error in query response with additional message. The response line
+    /// is kept in [`Error::server_line`].
     QueryResponseError(&'static str),
 
     /// Response from sonic server are wrong! Actually it may happen if you use
     /// unsupported sonic backend version. Please write issue to the github repo.
     WrongSonicResponse,
 
-    /// You cannot run the command in current channel.
+    /// You cannot run the command in current channel, or the server
+    /// doesn't support it.
     UnsupportedCommand((&'static str, Option<ChannelMode>)),
+
+    /// Command argument can't be sent to the server safely.
+    InvalidInput(&'static str),
+
+    /// Command line is longer than the buffer size that the server announced
+    /// on start. Nothing was sent to the server.
+    CommandTooLong {
+        /// Length of the command line in bytes.
+        length: usize,
+        /// Buffer size of the server in bytes.
+        max_buffer_size: usize,
+    },
+
+    /// Sonic server rejected the command with `ERR` response. Connection
+    /// stays open for other commands.
+    Server {
+        /// Code of the error.
+        code: ServerErrorCode,
+        /// Text in parentheses after the code, if the server sent it.
+        detail: Option<String>,
+    },
+
+    /// Sonic server closed connection with `ENDED <reason>` response.
+    ConnectionEnded(String),
+
+    /// Sonic server didn't answer in time. Channel is closed after this error,
+    /// except for read timeout of the multiplexed channel.
+    Timeout,
+
+    /// Certificates, private key or server name in TLS config are invalid.
+    ///
+    /// Note: This error kind requires enabling the `tls` feature.
+    #[cfg(feature = "tls")]
+    InvalidTlsConfig,
+
+    /// No channel became free in the pool within the acquire timeout.
+    ///
+    /// Note: This error kind requires enabling one of the runtime features.
+    #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
+    PoolTimeout,
+
+    /// Pool was closed and doesn't give out channels anymore.
+    ///
+    /// Note: This error kind requires enabling one of the runtime features.
+    #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
+    PoolClosed,
 }
 
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> std::result::Result<(), fmt::Error> {
-        match self.kind {
+        match &self.kind {
             ErrorKind::ConnectToServer => write!(f, "Cannot connect to server"),
             ErrorKind::WriteToStream => write!(f, "Cannot write data to stream"),
             ErrorKind::ReadStream => write!(f, "Cannot read sonic response from stream"),
@@ -75,7 +298,7 @@ impl fmt::Display for Error {
                 if let Some(channel_mode) = channel_mode {
                     write!(
                         f,
-                        "You cannot use `{}` command in {} sonic channel mode",
+                        "Sonic server doesn't support `{}` command in {} channel mode",
                         command_name, channel_mode
                     )
                 } else {
@@ -86,6 +309,32 @@ impl fmt::Display for Error {
                     )
                 }
             }
+            ErrorKind::InvalidInput(message) => write!(f, "Invalid input: {}", message),
+            ErrorKind::CommandTooLong {
+                length,
+                max_buffer_size,
+            } => write!(
+                f,
+                "Command of {} bytes doesn't fit into the server buffer of {} bytes",
+                length, max_buffer_size
+            ),
+            ErrorKind::Server {
+                code,
+                detail: Some(detail),
+            } => write!(f, "Sonic server rejected command: {}({})", code, detail),
+            ErrorKind::Server { code, detail: None } => {
+                write!(f, "Sonic server rejected command: {}", code)
+            }
+            ErrorKind::ConnectionEnded(reason) => {
+                write!(f, "Sonic server closed connection: {}", reason)
+            }
+            ErrorKind::Timeout => write!(f, "Sonic server didn't answer in time"),
+            #[cfg(feature = "tls")]
+            ErrorKind::InvalidTlsConfig => write!(f, "Invalid TLS configuration"),
+            #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
+            ErrorKind::PoolTimeout => write!(f, "Timed out waiting for a free channel in the pool"),
+            #[cfg(any(feature = "runtime-tokio", feature = "rt-async-io"))]
+            ErrorKind::PoolClosed => write!(f, "Connection pool is closed"),
         }
     }
 }
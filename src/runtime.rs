@@ -0,0 +1,138 @@
+//! Socket implementations of supported async runtimes.
+//!
+//! `runtime-tokio` opens sockets on the tokio reactor. `runtime-async-std` and
+//! `runtime-smol` both use `async-io`, which is the reactor that async-std and
+//! smol drive under the hood. If tokio and one of the others are enabled at the
+//! same time, tokio takes precedence.
+
+use crate::channels::{interleave_address_families, Transport};
+use std::future::Future;
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::time::Duration;
+
+/// Resolves address without blocking the executor and connects to resolved
+/// addresses one by one until the first successful connection. Timeout is
+/// applied to each address separately.
+pub(crate) async fn connect_tcp<A>(
+    addr: A,
+    connect_timeout: Option<Duration>,
+) -> io::Result<(impl Transport, SocketAddr)>
+where
+    A: ToSocketAddrs + Send + 'static,
+{
+    let addrs = resolve(addr).await?;
+
+    let mut last_err = None;
+    for addr in interleave_address_families(addrs) {
+        let res = match connect_timeout {
+            Some(connect_timeout) => timeout(connect_timeout, connect_socket(addr))
+                .await
+                .unwrap_or_else(|| Err(io::ErrorKind::TimedOut.into())),
+            None => connect_socket(addr).await,
+        };
+        match res {
+            Ok(stream) => return Ok((stream, addr)),
+            Err(err) => last_err = Some(err),
+        }
+    }
+
+    Err(last_err.unwrap_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "could not resolve to any address",
+        )
+    }))
+}
+
+/// Awaits future no longer than duration. Returns `None` if time is out.
+#[cfg(feature = "runtime-tokio")]
+pub(crate) async fn timeout<F: Future>(duration: Duration, future: F) -> Option<F::Output> {
+    tokio::time::timeout(duration, future).await.ok()
+}
+
+/// Waits for the given duration without blocking the executor.
+#[cfg(feature = "runtime-tokio")]
+pub(crate) async fn sleep(duration: Duration) {
+    tokio::time::sleep(duration).await
+}
+
+/// Runs future in background on the tokio runtime of the current task.
+#[cfg(feature = "runtime-tokio")]
+pub(crate) fn spawn<F>(future: F)
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    tokio::spawn(future);
+}
+
+#[cfg(feature = "runtime-tokio")]
+async fn resolve<A>(addr: A) -> io::Result<Vec<SocketAddr>>
+where
+    A: ToSocketAddrs + Send + 'static,
+{
+    tokio::task::spawn_blocking(move || addr.to_socket_addrs().map(Iterator::collect))
+        .await
+        .map_err(io::Error::other)?
+}
+
+#[cfg(feature = "runtime-tokio")]
+async fn connect_socket(addr: SocketAddr) -> io::Result<impl Transport> {
+    use tokio_util::compat::TokioAsyncReadCompatExt;
+
+    let stream = tokio::net::TcpStream::connect(addr).await?;
+    Ok(stream.compat())
+}
+
+/// Awaits future no longer than duration. Returns `None` if time is out.
+#[cfg(all(feature = "rt-async-io", not(feature = "runtime-tokio")))]
+pub(crate) async fn timeout<F: Future>(duration: Duration, future: F) -> Option<F::Output> {
+    let timer = async {
+        async_io::Timer::after(duration).await;
+        None
+    };
+    futures_lite::future::or(async { Some(future.await) }, timer).await
+}
+
+/// Waits for the given duration without blocking the executor.
+#[cfg(all(feature = "rt-async-io", not(feature = "runtime-tokio")))]
+pub(crate) async fn sleep(duration: Duration) {
+    async_io::Timer::after(duration).await;
+}
+
+/// Runs future in background on the global executor, which is shared with
+/// async-std.
+#[cfg(all(feature = "rt-async-io", not(feature = "runtime-tokio")))]
+pub(crate) fn spawn<F>(future: F)
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    async_global_executor::spawn(future).detach();
+}
+
+#[cfg(all(feature = "rt-async-io", not(feature = "runtime-tokio")))]
+async fn resolve<A>(addr: A) -> io::Result<Vec<SocketAddr>>
+where
+    A: ToSocketAddrs + Send + 'static,
+{
+    unblock::unblock(move || addr.to_socket_addrs().map(Iterator::collect)).await
+}
+
+#[cfg(all(feature = "rt-async-io", not(feature = "runtime-tokio")))]
+async fn connect_socket(addr: SocketAddr) -> io::Result<impl Transport> {
+    async_io::Async::<std::net::TcpStream>::connect(addr).await
+}
+
+/// Runs future to completion on the selected runtime.
+#[cfg(test)]
+pub(crate) fn block_on<F: Future>(future: F) -> F::Output {
+    #[cfg(feature = "runtime-tokio")]
+    return tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(future);
+
+    #[cfg(not(feature = "runtime-tokio"))]
+    return async_io::block_on(future);
+}
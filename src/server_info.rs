@@ -0,0 +1,100 @@
+use crate::channels::ChannelMode;
+use std::fmt;
+
+/// Version of the sonic server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ServerVersion {
+    /// Major version.
+    pub major: u32,
+    /// Minor version.
+    pub minor: u32,
+    /// Patch version.
+    pub patch: u32,
+}
+
+impl ServerVersion {
+    /// Creates version from its parts.
+    pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+        ServerVersion {
+            major,
+            minor,
+            patch,
+        }
+    }
+
+    /// Parses version from the `CONNECTED` banner, for example
+    /// `<sonic-server v1.3.0>`. Suffix of the patch version is ignored.
+    ///
+    /// ```rust
+    /// use sonic_channel::ServerVersion;
+    ///
+    /// assert_eq!(
+    ///     ServerVersion::from_banner("<sonic-server v1.4.0-beta>"),
+    ///     Some(ServerVersion::new(1, 4, 0))
+    /// );
+    /// assert_eq!(ServerVersion::from_banner("<proxy>"), None);
+    /// ```
+    pub fn from_banner(banner: &str) -> Option<Self> {
+        let version = banner
+            .trim_matches(&['<', '>'][..])
+            .split_whitespace()
+            .find_map(|word| word.strip_prefix('v'))?;
+        let mut parts = version.splitn(3, '.');
+        let major = parts.next()?.parse().ok()?;
+        let minor = parts.next()?.parse().ok()?;
+        let patch = parts.next()?;
+        let digits = patch
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(patch.len());
+        let patch = patch[..digits].parse().ok()?;
+        Some(ServerVersion::new(major, minor, patch))
+    }
+}
+
+impl fmt::Display for ServerVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+/// Commands that appeared in later sonic versions. Servers that don't
+/// answer `HELP` are checked by this list.
+const MIN_VERSIONS: &[(&str, ServerVersion)] = &[("LIST", ServerVersion::new(1, 4, 0))];
+
+/// What the server told about itself on connect and start.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ServerInfo {
+    /// Version of the server from the `CONNECTED` banner, `None` if the
+    /// banner has no version.
+    pub version: Option<ServerVersion>,
+    /// Version of the sonic protocol from `STARTED`.
+    pub protocol_version: usize,
+    /// Maximum size of one command in bytes from `STARTED`.
+    pub max_buffer_size: usize,
+    /// Mode of the channel.
+    pub mode: ChannelMode,
+    /// Commands of the mode from `HELP commands`, `None` if the server
+    /// didn't answer it.
+    pub commands: Option<Vec<String>>,
+}
+
+impl ServerInfo {
+    /// Returns `true` if the server supports the command, for example
+    /// `"LIST"`. If the server didn't list its commands, the command is
+    /// checked by the server version, and unknown servers are expected to
+    /// support everything.
+    pub fn supports(&self, command: &str) -> bool {
+        if let Some(commands) = &self.commands {
+            return commands
+                .iter()
+                .any(|name| name.eq_ignore_ascii_case(command));
+        }
+        match self.version {
+            Some(version) => MIN_VERSIONS
+                .iter()
+                .all(|(name, min)| *name != command || version >= *min),
+            None => true,
+        }
+    }
+}
@@ -0,0 +1,238 @@
+//! Helpers shared by unit tests.
+#![allow(dead_code)]
+
+use crate::channels::ChannelMode;
+use futures_lite::io::{AsyncRead, AsyncWrite, Cursor};
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+use std::thread;
+
+/// In-memory transport that replays server responses in chunks of the
+/// given size and records requests.
+pub(crate) struct MemoryTransport {
+    responses: Cursor<Vec<u8>>,
+    requests: Arc<Mutex<Vec<u8>>>,
+    writes: Arc<Mutex<Vec<usize>>>,
+    chunk_size: usize,
+}
+
+impl MemoryTransport {
+    /// Creates transport that returns all responses with the first read.
+    pub(crate) fn new(responses: &[u8]) -> Self {
+        Self::chunked(responses, usize::MAX)
+    }
+
+    /// Creates transport that returns at most `chunk_size` bytes per read.
+    pub(crate) fn chunked(responses: &[u8], chunk_size: usize) -> Self {
+        MemoryTransport {
+            responses: Cursor::new(responses.to_vec()),
+            requests: Arc::default(),
+            writes: Arc::default(),
+            chunk_size,
+        }
+    }
+
+    /// Returns all bytes that client wrote to the transport.
+    pub(crate) fn requests(&self) -> Arc<Mutex<Vec<u8>>> {
+        self.requests.clone()
+    }
+
+    /// Returns sizes of all writes to the transport.
+    pub(crate) fn writes(&self) -> Arc<Mutex<Vec<usize>>> {
+        self.writes.clone()
+    }
+}
+
+impl AsyncRead for MemoryTransport {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let len = self.chunk_size.min(buf.len());
+        Pin::new(&mut self.responses).poll_read(cx, &mut buf[..len])
+    }
+}
+
+impl AsyncWrite for MemoryTransport {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.requests.lock().unwrap().extend_from_slice(buf);
+        self.writes.lock().unwrap().push(buf.len());
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// Returns any channel mode that is enabled by crate features.
+pub(crate) fn any_mode() -> ChannelMode {
+    #[cfg(feature = "search")]
+    return ChannelMode::Search;
+    #[cfg(all(feature = "ingest", not(feature = "search")))]
+    return ChannelMode::Ingest;
+    #[cfg(all(feature = "control", not(any(feature = "search", feature = "ingest"))))]
+    return ChannelMode::Control;
+}
+
+/// Returns `HELP commands` answer of sonic v1.4.0 for the mode.
+pub(crate) fn help_answer(mode: &str) -> &'static str {
+    match mode {
+        "search" => "RESULT commands(QUERY, SUGGEST, LIST, PING, HELP, QUIT)\r\n",
+        "ingest" => {
+            "RESULT commands(PUSH, POP, COUNT, FLUSHC, FLUSHB, FLUSHO, PING, HELP, QUIT)\r\n"
+        }
+        _ => "RESULT commands(TRIGGER, INFO, PING, HELP, QUIT)\r\n",
+    }
+}
+
+/// Sends banner and answers `START` in the requested mode. `HELP` is sent
+/// right after the first start, but not after reconnect.
+fn handshake(stream: &mut TcpStream, reader: &mut BufReader<TcpStream>, help: bool) {
+    let mut line = String::new();
+    stream
+        .write_all(b"CONNECTED <sonic-server v1.4.0>\r\n")
+        .unwrap();
+    reader.read_line(&mut line).unwrap();
+    let mode = line.split_whitespace().nth(1).unwrap().to_string();
+    let started = format!("STARTED {} protocol(1) buffer(20000)\r\n", mode);
+    stream.write_all(started.as_bytes()).unwrap();
+
+    if help {
+        line.clear();
+        reader.read_line(&mut line).unwrap();
+        stream.write_all(help_answer(&mode).as_bytes()).unwrap();
+    }
+}
+
+/// Starts fake sonic server that accepts one connection, runs start command
+/// and then answers `PONG` to the first `pongs` commands. After that the
+/// server reads commands and never answers.
+pub(crate) fn hanging_sonic_server(pongs: usize) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut line = String::new();
+
+        handshake(&mut stream, &mut reader, true);
+
+        for _ in 0..pongs {
+            line.clear();
+            reader.read_line(&mut line).unwrap();
+            stream.write_all(b"PONG\r\n").unwrap();
+        }
+
+        line.clear();
+        while reader.read_line(&mut line).unwrap_or(0) > 0 {}
+    });
+    addr
+}
+
+/// Starts fake sonic server that accepts one connection per script. After
+/// start command each request of the connection gets the next answer from
+/// the script. When the script is over, the server drops the connection.
+pub(crate) fn scripted_sonic_server(scripts: Vec<Vec<&'static str>>) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    thread::spawn(move || {
+        for (i, script) in scripts.into_iter().enumerate() {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut line = String::new();
+
+            handshake(&mut stream, &mut reader, i == 0);
+
+            for answer in script {
+                line.clear();
+                reader.read_line(&mut line).unwrap();
+                stream.write_all(answer.as_bytes()).unwrap();
+            }
+        }
+    });
+    addr
+}
+
+/// Starts fake sonic server that accepts any number of connections, answers
+/// `QUERY` with its terms as object ids, `QUIT` with `ENDED` and `PONG` to
+/// all other commands.
+pub(crate) fn ponging_sonic_server() -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            let mut stream = stream.unwrap();
+            thread::spawn(move || {
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut line = String::new();
+
+                handshake(&mut stream, &mut reader, true);
+
+                line.clear();
+                while reader.read_line(&mut line).unwrap_or(0) > 0 {
+                    if line.starts_with("QUIT") {
+                        let _ = stream.write_all(b"ENDED quit\r\n");
+                        return;
+                    }
+                    let answer = match line.split('"').nth(1) {
+                        Some(terms) if line.starts_with("QUERY") => {
+                            format!("PENDING q\r\nEVENT QUERY q {}\r\n", terms)
+                        }
+                        _ => String::from("PONG\r\n"),
+                    };
+                    if stream.write_all(answer.as_bytes()).is_err() {
+                        return;
+                    }
+                    line.clear();
+                }
+            });
+        }
+    });
+    addr
+}
+
+/// Starts fake sonic server that accepts one connection, reads `QUERY`
+/// commands by `batch` and answers `PENDING` to each of them, then sends
+/// events with the query terms as object ids in reverse order.
+pub(crate) fn multiplexing_sonic_server(batch: usize) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+        let mut line = String::new();
+
+        handshake(&mut stream, &mut reader, true);
+
+        for id in (0..).step_by(batch) {
+            let mut events = Vec::new();
+            for id in id..id + batch {
+                line.clear();
+                if reader.read_line(&mut line).unwrap_or(0) == 0 {
+                    return;
+                }
+                let terms = line.split('"').nth(1).unwrap_or_default();
+                events.push(format!("EVENT QUERY q{} {}\r\n", id, terms));
+                let pending = format!("PENDING q{}\r\n", id);
+                stream.write_all(pending.as_bytes()).unwrap();
+            }
+            for event in events.iter().rev() {
+                stream.write_all(event.as_bytes()).unwrap();
+            }
+        }
+    });
+    addr
+}
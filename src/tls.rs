@@ -0,0 +1,315 @@
+use crate::result::*;
+use futures_rustls::rustls::{self, ClientConfig, RootCertStore};
+use rustls_pki_types::pem::PemObject;
+use rustls_pki_types::{CertificateDer, PrivateKeyDer, ServerName};
+use std::convert::TryFrom;
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// TLS settings for connections to sonic server behind a TLS terminator
+/// like stunnel or haproxy.
+///
+/// By default the server certificate is verified against Mozilla root
+/// certificates. Server name is never guessed from the address, so
+/// [`TlsConfig::server_name`] is required. It can be a hostname or an IP
+/// address, whatever the server certificate is issued for.
+///
+/// Note: This type requires enabling the `tls` feature.
+///
+/// ```rust
+/// use sonic_channel::TlsConfig;
+///
+/// let config = TlsConfig::new().server_name("sonic.example.com");
+/// ```
+#[derive(Clone)]
+pub struct TlsConfig {
+    roots: RootCertStore,
+    client_auth: Option<(Vec<CertificateDer<'static>>, Arc<PrivateKeyDer<'static>>)>,
+    server_name: Option<String>,
+}
+
+impl Default for TlsConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Debug for TlsConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TlsConfig")
+            .field("roots", &self.roots.len())
+            .field("client_auth", &self.client_auth.is_some())
+            .field("server_name", &self.server_name)
+            .finish()
+    }
+}
+
+impl TlsConfig {
+    /// Creates TLS config that trusts Mozilla root certificates.
+    pub fn new() -> Self {
+        TlsConfig {
+            roots: RootCertStore {
+                roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+            },
+            client_auth: None,
+            server_name: None,
+        }
+    }
+
+    /// Creates TLS config without any trusted root certificate. Use it with
+    /// [`TlsConfig::add_root_certificates_pem`] if your sonic server uses
+    /// certificate from a private CA.
+    pub fn without_roots() -> Self {
+        TlsConfig {
+            roots: RootCertStore::empty(),
+            client_auth: None,
+            server_name: None,
+        }
+    }
+
+    /// Trusts all certificates from PEM encoded data.
+    pub fn add_root_certificates_pem(mut self, pem: &[u8]) -> Result<Self> {
+        let mut added = 0;
+        for cert in CertificateDer::pem_slice_iter(pem) {
+            let cert =
+                cert.map_err(|err| Error::new(ErrorKind::InvalidTlsConfig).with_source(err))?;
+            self.roots
+                .add(cert)
+                .map_err(|err| Error::new(ErrorKind::InvalidTlsConfig).with_source(err))?;
+            added += 1;
+        }
+
+        if added == 0 {
+            return Err(Error::new(ErrorKind::InvalidTlsConfig));
+        }
+
+        Ok(self)
+    }
+
+    /// Authenticates client with certificate chain and private key in PEM format.
+    pub fn client_certificate_pem(mut self, cert_chain: &[u8], key: &[u8]) -> Result<Self> {
+        let certs = CertificateDer::pem_slice_iter(cert_chain)
+            .collect::<std::result::Result<Vec<_>, _>>()
+            .map_err(|err| Error::new(ErrorKind::InvalidTlsConfig).with_source(err))?;
+        let key = PrivateKeyDer::from_pem_slice(key)
+            .map_err(|err| Error::new(ErrorKind::InvalidTlsConfig).with_source(err))?;
+
+        if certs.is_empty() {
+            return Err(Error::new(ErrorKind::InvalidTlsConfig));
+        }
+
+        self.client_auth = Some((certs, Arc::new(key)));
+        Ok(self)
+    }
+
+    /// Sets name that is sent to the server in SNI and is used to verify
+    /// server certificate. Connection fails with
+    /// [`ErrorKind::InvalidTlsConfig`] if the name is not set.
+    pub fn server_name<S: Into<String>>(mut self, name: S) -> Self {
+        self.server_name = Some(name.into());
+        self
+    }
+
+    pub(crate) fn client_config(&self) -> Result<ClientConfig> {
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+        let builder = ClientConfig::builder_with_provider(provider)
+            .with_safe_default_protocol_versions()
+            .map_err(|err| Error::new(ErrorKind::InvalidTlsConfig).with_source(err))?
+            .with_root_certificates(self.roots.clone());
+
+        match self.client_auth.as_ref() {
+            Some((certs, key)) => builder
+                .with_client_auth_cert(certs.clone(), key.clone_key())
+                .map_err(|err| Error::new(ErrorKind::InvalidTlsConfig).with_source(err)),
+            None => Ok(builder.with_no_client_auth()),
+        }
+    }
+
+    pub(crate) fn required_server_name(&self) -> Result<ServerName<'static>> {
+        match self.server_name.as_ref() {
+            Some(name) => ServerName::try_from(name.clone())
+                .map_err(|err| Error::new(ErrorKind::InvalidTlsConfig).with_source(err)),
+            None => Err(Error::new(ErrorKind::InvalidTlsConfig)),
+        }
+    }
+}
+
+pub(crate) async fn connect<A>(
+    addr: A,
+    config: &TlsConfig,
+    connect_timeout: Option<Duration>,
+) -> Result<impl crate::channels::Transport>
+where
+    A: std::net::ToSocketAddrs + Send + 'static,
+{
+    let connector = futures_rustls::TlsConnector::from(Arc::new(config.client_config()?));
+    let server_name = config.required_server_name()?;
+
+    let (stream, _) = crate::runtime::connect_tcp(addr, connect_timeout)
+        .await
+        .map_err(|err| Error::new(ErrorKind::ConnectToServer).with_source(err))?;
+
+    let handshake = connector.connect(server_name, stream);
+    let res = match connect_timeout {
+        Some(connect_timeout) => crate::runtime::timeout(connect_timeout, handshake)
+            .await
+            .ok_or_else(|| Error::new(ErrorKind::Timeout))?,
+        None => handshake.await,
+    };
+    res.map_err(|err| Error::new(ErrorKind::ConnectToServer).with_source(err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::channels::{SearchChannel, SonicChannel};
+    use crate::runtime::block_on;
+    use crate::test_utils::help_answer;
+    use rcgen::{BasicConstraints, Certificate, CertificateParams, IsCa, KeyPair};
+    use rustls::server::WebPkiClientVerifier;
+    use rustls::{ServerConfig, ServerConnection, StreamOwned};
+    use std::io::{Read, Write};
+    use std::net::{SocketAddr, TcpListener};
+    use std::thread;
+
+    struct Pki {
+        ca: Certificate,
+        ca_key: KeyPair,
+    }
+
+    impl Pki {
+        fn new() -> Self {
+            let ca_key = KeyPair::generate().unwrap();
+            let mut params = CertificateParams::new(Vec::<String>::new()).unwrap();
+            params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+            let ca = params.self_signed(&ca_key).unwrap();
+            Pki { ca, ca_key }
+        }
+
+        fn issue(&self, name: &str) -> (Certificate, KeyPair) {
+            let key = KeyPair::generate().unwrap();
+            let params = CertificateParams::new(vec![name.to_string()]).unwrap();
+            let cert = params.signed_by(&key, &self.ca, &self.ca_key).unwrap();
+            (cert, key)
+        }
+    }
+
+    fn read_line<R: Read>(reader: &mut R) -> String {
+        let mut line = Vec::new();
+        let mut byte = [0];
+        while reader.read(&mut byte).unwrap() == 1 {
+            line.push(byte[0]);
+            if byte[0] == b'\n' {
+                break;
+            }
+        }
+        String::from_utf8(line).unwrap()
+    }
+
+    /// Starts fake sonic server behind TLS that requires client certificate
+    /// from the same CA and answers one `START` and one `PING` command.
+    fn fake_sonic_server(pki: &Pki) -> (SocketAddr, thread::JoinHandle<Vec<String>>) {
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+        let (cert, key) = pki.issue("sonic.test");
+
+        let mut roots = RootCertStore::empty();
+        roots.add(pki.ca.der().clone()).unwrap();
+        let verifier = WebPkiClientVerifier::builder_with_provider(roots.into(), provider.clone())
+            .build()
+            .unwrap();
+        let config = ServerConfig::builder_with_provider(provider)
+            .with_safe_default_protocol_versions()
+            .unwrap()
+            .with_client_cert_verifier(verifier)
+            .with_single_cert(
+                vec![cert.der().clone()],
+                PrivateKeyDer::Pkcs8(key.serialize_der().into()),
+            )
+            .unwrap();
+
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let addr = listener.local_addr().unwrap();
+        let handle = thread::spawn(move || {
+            let (tcp, _) = listener.accept().unwrap();
+            let conn = ServerConnection::new(Arc::new(config)).unwrap();
+            let mut stream = StreamOwned::new(conn, tcp);
+
+            let mut requests = Vec::new();
+            if stream
+                .write_all(b"CONNECTED <sonic-server v1.3.0>\r\n")
+                .is_err()
+            {
+                return requests;
+            }
+            requests.push(read_line(&mut stream));
+            stream
+                .write_all(b"STARTED search protocol(1) buffer(20000)\r\n")
+                .unwrap();
+            requests.push(read_line(&mut stream));
+            stream.write_all(help_answer("search").as_bytes()).unwrap();
+            requests.push(read_line(&mut stream));
+            stream.write_all(b"PONG\r\n").unwrap();
+            requests
+        });
+
+        (addr, handle)
+    }
+
+    #[test]
+    fn start_search_channel_over_tls() {
+        let pki = Pki::new();
+        let (addr, server) = fake_sonic_server(&pki);
+        let (client_cert, client_key) = pki.issue("client");
+
+        let config = TlsConfig::without_roots()
+            .add_root_certificates_pem(pki.ca.pem().as_bytes())
+            .unwrap()
+            .client_certificate_pem(
+                client_cert.pem().as_bytes(),
+                client_key.serialize_pem().as_bytes(),
+            )
+            .unwrap()
+            .server_name("sonic.test");
+
+        let pong = block_on(async {
+            let channel = SearchChannel::start_tls(addr, "secret", config).await?;
+            channel.ping().await
+        })
+        .unwrap();
+
+        assert!(pong);
+        assert_eq!(
+            server.join().unwrap(),
+            vec!["START search secret\r\n", "HELP commands\r\n", "PING\r\n"]
+        );
+    }
+
+    #[test]
+    fn reject_server_from_unknown_ca() {
+        let (addr, _server) = fake_sonic_server(&Pki::new());
+
+        let config = TlsConfig::without_roots()
+            .add_root_certificates_pem(Pki::new().ca.pem().as_bytes())
+            .unwrap()
+            .server_name("sonic.test");
+
+        let res = block_on(SearchChannel::start_tls(addr, "secret", config));
+        assert!(res.is_err());
+    }
+
+    #[test]
+    fn require_server_name() {
+        let config = TlsConfig::without_roots();
+
+        // The name is checked before anything is connected.
+        let res = block_on(SearchChannel::start_tls("127.0.0.1:1", "secret", config));
+        assert!(matches!(res.unwrap_err().kind, ErrorKind::InvalidTlsConfig));
+    }
+
+    #[test]
+    fn reject_invalid_pem() {
+        let res = TlsConfig::without_roots().add_root_certificates_pem(b"not a certificate");
+        assert!(res.is_err());
+    }
+}
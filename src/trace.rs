@@ -0,0 +1,176 @@
+//! Spans of commands for the `tracing` feature. Without the feature all
+//! functions do nothing and are optimized away.
+//!
+//! Each command gets the `sonic_command` span with channel mode, command
+//! name and redacted request line. Latency and size of the response are
+//! recorded when the command is finished. Password of `START` is never
+//! recorded, quoted text of commands only if
+//! [`ChannelOptions::trace_payloads`] is enabled.
+
+use crate::channels::ChannelMode;
+use crate::options::ChannelOptions;
+use crate::result::Result;
+#[cfg(feature = "tracing")]
+use std::borrow::Cow;
+use std::future::Future;
+#[cfg(feature = "tracing")]
+use std::time::Instant;
+#[cfg(feature = "tracing")]
+use tracing::{field, Instrument, Span};
+
+/// Text that is recorded instead of passwords and payloads.
+#[cfg(feature = "tracing")]
+const REDACTED: &str = "<redacted>";
+
+/// Span of one command or pipeline.
+pub(crate) struct CommandSpan {
+    #[cfg(feature = "tracing")]
+    span: Span,
+    #[cfg(feature = "tracing")]
+    started: Instant,
+}
+
+#[cfg(feature = "tracing")]
+impl CommandSpan {
+    /// Creates span of the encoded request. Mode is unknown until start.
+    pub(crate) fn new(mode: Option<ChannelMode>, request: &str, options: &ChannelOptions) -> Self {
+        let name = request.split_whitespace().next().unwrap_or_default();
+        let span = tracing::debug_span!(
+            "sonic_command",
+            mode = field::Empty,
+            command = name,
+            request = %redact(request, options.trace_payloads),
+            response_bytes = field::Empty,
+            latency_us = field::Empty,
+        );
+        Self::with_mode(span, mode)
+    }
+
+    /// Creates span of the pipeline with the count of commands.
+    pub(crate) fn pipeline(mode: Option<ChannelMode>, commands: usize) -> Self {
+        let span = tracing::debug_span!(
+            "sonic_pipeline",
+            mode = field::Empty,
+            commands,
+            response_bytes = field::Empty,
+            latency_us = field::Empty,
+        );
+        Self::with_mode(span, mode)
+    }
+
+    fn with_mode(span: Span, mode: Option<ChannelMode>) -> Self {
+        if let Some(mode) = mode {
+            span.record("mode", mode.to_str());
+        }
+        CommandSpan {
+            span,
+            started: Instant::now(),
+        }
+    }
+
+    /// Runs blocking IO of the command inside the span.
+    #[cfg(feature = "blocking")]
+    pub(crate) fn in_scope<T>(&self, f: impl FnOnce() -> T) -> T {
+        self.span.in_scope(f)
+    }
+
+    /// Runs async IO of the command inside the span.
+    pub(crate) async fn instrument<F: Future>(&self, io: F) -> F::Output {
+        io.instrument(self.span.clone()).await
+    }
+
+    /// Records latency and result of the command.
+    pub(crate) fn finish<T>(self, res: Result<T>) -> Result<T> {
+        let latency = self.started.elapsed();
+        self.span.record("latency_us", latency.as_micros() as u64);
+        match &res {
+            Ok(_) => tracing::debug!(parent: &self.span, ?latency, "command finished"),
+            Err(err) => {
+                tracing::debug!(parent: &self.span, ?latency, error = %err, "command failed")
+            }
+        }
+        res
+    }
+}
+
+#[cfg(not(feature = "tracing"))]
+impl CommandSpan {
+    pub(crate) fn new(
+        _mode: Option<ChannelMode>,
+        _request: &str,
+        _options: &ChannelOptions,
+    ) -> Self {
+        CommandSpan {}
+    }
+
+    pub(crate) fn pipeline(_mode: Option<ChannelMode>, _commands: usize) -> Self {
+        CommandSpan {}
+    }
+
+    #[cfg(feature = "blocking")]
+    pub(crate) fn in_scope<T>(&self, f: impl FnOnce() -> T) -> T {
+        f()
+    }
+
+    pub(crate) async fn instrument<F: Future>(&self, io: F) -> F::Output {
+        io.await
+    }
+
+    pub(crate) fn finish<T>(self, res: Result<T>) -> Result<T> {
+        res
+    }
+}
+
+/// Adds size of the received response to the span of the current command.
+pub(crate) fn record_response(_bytes: usize) {
+    #[cfg(feature = "tracing")]
+    Span::current().record("response_bytes", _bytes);
+}
+
+/// Hides password of `START` and, unless payloads are allowed, quoted text
+/// of `PUSH`, `POP`, `QUERY` and `SUGGEST`. Line terminator is dropped.
+#[cfg(feature = "tracing")]
+pub(crate) fn redact(request: &str, payloads: bool) -> Cow<'_, str> {
+    let request = request.trim_end_matches(&['\r', '\n'][..]);
+    if let Some(rest) = request.strip_prefix("START ") {
+        let mode = rest.split(' ').next().unwrap_or_default();
+        return Cow::Owned(format!("START {} {}", mode, REDACTED));
+    }
+    if payloads {
+        return Cow::Borrowed(request);
+    }
+    match (request.find('"'), request.rfind('"')) {
+        (Some(start), Some(end)) if start < end => Cow::Owned(format!(
+            "{}\"{}\"{}",
+            &request[..start],
+            REDACTED,
+            &request[end + 1..]
+        )),
+        _ => Cow::Borrowed(request),
+    }
+}
+
+#[cfg(all(test, feature = "tracing"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redact_passwords_and_payloads() {
+        assert_eq!(
+            redact("START search SecretPassword\r\n", true),
+            "START search <redacted>"
+        );
+        assert_eq!(
+            redact("PUSH c b o \"my \\\"best\\\" recipe\" LANG(eng)\r\n", false),
+            "PUSH c b o \"<redacted>\" LANG(eng)"
+        );
+        assert_eq!(
+            redact("QUERY c b \"beef\" LIMIT(10)\r\n", true),
+            "QUERY c b \"beef\" LIMIT(10)"
+        );
+        assert_eq!(
+            redact("LIST c b LIMIT(10)\r\n", false),
+            "LIST c b LIMIT(10)"
+        );
+    }
+}